@@ -0,0 +1,220 @@
+//! Working backwards from the rounds of a game to the bag they came from.
+//!
+//! Every round is modelled as drawing `round.total()` cubes from the bag
+//! without replacement, after which the cubes are put back before the next
+//! round. The probability of a single round is then multivariate
+//! hypergeometric, and the rounds of a game are independent of each other.
+
+use crate::{Bag, CubeGame, CubeSet};
+
+/// Every bag holding exactly `total` cubes, split across the colours seen in
+/// `game`, that the game is possible with.
+pub fn candidate_bags(game: &CubeGame, total: u32) -> Vec<Bag> {
+    let minimal = game.minimal_bag();
+    let colors = minimal.cubes().iter().collect::<Vec<_>>();
+
+    let Some(spare) = total.checked_sub(minimal.cubes().total()) else {
+        return vec![];
+    };
+
+    let mut bags = vec![];
+    let mut extra = vec![0; colors.len()];
+    distribute(spare, 0, &mut extra, &mut |extra| {
+        let bag: Bag = colors
+            .iter()
+            .zip(extra)
+            .map(|(&(color, min), &extra)| (color, min + extra))
+            .collect::<CubeSet>()
+            .into();
+
+        if game.is_possible_with(&bag) {
+            bags.push(bag);
+        }
+    });
+
+    bags
+}
+
+/// The natural log of the probability of drawing every round of `game` from
+/// `bag`. Impossible games give negative infinity.
+pub fn log_likelihood(game: &CubeGame, bag: &Bag) -> f64 {
+    game.rounds()
+        .iter()
+        .map(|round| round_log_likelihood(round, bag))
+        .sum()
+}
+
+/// The bag of `total` cubes that makes the rounds of `game` most likely,
+/// along with that likelihood. Returns `None` if `total` is smaller than the
+/// minimal bag.
+pub fn most_likely_bag(game: &CubeGame, total: u32) -> Option<(Bag, f64)> {
+    candidate_bags(game, total)
+        .into_iter()
+        .map(|bag| {
+            let log_likelihood = log_likelihood(game, &bag);
+            (bag, log_likelihood)
+        })
+        .fold(
+            None,
+            |best: Option<(Bag, f64)>, (bag, log_likelihood)| match best {
+                Some((_, best_log_likelihood)) if best_log_likelihood >= log_likelihood => best,
+                _ => Some((bag, log_likelihood)),
+            },
+        )
+        .map(|(bag, log_likelihood)| (bag, log_likelihood.exp()))
+}
+
+/// Estimates how likely the rounds of a game are by actually drawing cubes
+/// out of a bag, as a cross-check for [`log_likelihood`].
+#[derive(Debug)]
+pub struct Simulator {
+    rng: Rng,
+    trials: u32,
+}
+
+impl Simulator {
+    pub fn create(seed: u64, trials: u32) -> Self {
+        assert!(trials > 0);
+
+        Self {
+            rng: Rng(seed),
+            trials,
+        }
+    }
+
+    /// The estimated probability of the rounds of `game` being drawn from
+    /// `bag`. Each round is simulated separately and the estimates are
+    /// multiplied, since the chance of matching a whole game in one trial
+    /// quickly becomes too small to sample.
+    pub fn estimate(&mut self, game: &CubeGame, bag: &Bag) -> f64 {
+        game.rounds()
+            .iter()
+            .map(|round| self.estimate_round(round, bag))
+            .product()
+    }
+
+    /// Runs [`Simulator::estimate`] for each of `bags`.
+    pub fn estimate_all(&mut self, game: &CubeGame, bags: &[Bag]) -> Vec<f64> {
+        bags.iter().map(|bag| self.estimate(game, bag)).collect()
+    }
+
+    fn estimate_round(&mut self, round: &CubeSet, bag: &Bag) -> f64 {
+        if !round.fits_in(bag) {
+            return 0.0;
+        }
+
+        let colors = bag.cubes().iter().collect::<Vec<_>>();
+        let mut cubes = colors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &(_, count))| (0..count).map(move |_| i))
+            .collect::<Vec<_>>();
+
+        let draw_count = round.total() as usize;
+        let mut hits = 0;
+
+        for _ in 0..self.trials {
+            // Partial Fisher-Yates: the first `draw_count` cubes are the draw.
+            for i in 0..draw_count {
+                let j = i + self.rng.below((cubes.len() - i) as u64) as usize;
+                cubes.swap(i, j);
+            }
+
+            let mut drawn = vec![0; colors.len()];
+            for &cube in cubes[..draw_count].iter() {
+                drawn[cube] += 1;
+            }
+
+            let matches = colors
+                .iter()
+                .zip(drawn)
+                .all(|(&(color, _), count)| round.count(color) == count);
+
+            if matches {
+                hits += 1;
+            }
+        }
+
+        hits as f64 / self.trials as f64
+    }
+}
+
+/// A small SplitMix64 generator, so that simulations are reproducible from a
+/// seed.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+fn round_log_likelihood(round: &CubeSet, bag: &Bag) -> f64 {
+    if !round.fits_in(bag) {
+        return f64::NEG_INFINITY;
+    }
+
+    let favourable: f64 = round
+        .iter()
+        .map(|(color, count)| ln_choose(bag.count(color), count))
+        .sum();
+
+    favourable - ln_choose(bag.cubes().total(), round.total())
+}
+
+fn ln_choose(n: u32, k: u32) -> f64 {
+    let k = k.min(n - k);
+
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+/// Calls `f` with every way of splitting `remaining` across the slots of
+/// `extra` from `index` onwards.
+fn distribute(remaining: u32, index: usize, extra: &mut [u32], f: &mut impl FnMut(&[u32])) {
+    if extra.is_empty() {
+        if remaining == 0 {
+            f(extra);
+        }
+        return;
+    }
+
+    if index == extra.len() - 1 {
+        extra[index] = remaining;
+        f(extra);
+        return;
+    }
+
+    for count in 0..=remaining {
+        extra[index] = count;
+        distribute(remaining - count, index + 1, extra, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_likelihood_is_hypergeometric() {
+        let bag = Bag::create("red = 3\nblue = 2");
+        let game = CubeGame::create("Game 1: 1 red, 1 blue; 2 red");
+
+        // C(3,1) C(2,1) / C(5,2) for the first round, C(3,2) / C(5,2) for the
+        // second.
+        let expected = (6.0_f64 / 10.0 * 3.0 / 10.0).ln();
+        assert!((log_likelihood(&game, &bag) - expected).abs() < 1e-12);
+
+        let game = CubeGame::create("Game 2: 4 red");
+        assert_eq!(log_likelihood(&game, &bag), f64::NEG_INFINITY);
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+pub mod inference;
+pub mod query;
+
+#[derive(Debug)]
+pub struct CubeGame {
+    pub id: u32,
+    rounds: Vec<CubeSet>,
+}
+
+/// A count of cubes per colour. Colours are kept in a sorted map so that
+/// iteration order (and therefore any output) is stable.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct CubeSet(BTreeMap<String, u32>);
+
+/// The contents of the bag that the elf draws cubes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag(CubeSet);
+
+impl CubeGame {
+    pub fn create(input: &str) -> CubeGame {
+        let parts = input.split(':').collect::<Vec<_>>();
+        let id_part = parts.first().unwrap();
+        let rest = parts.last().unwrap();
+
+        let id = id_part.trim_start_matches("Game ").parse().unwrap();
+        let rounds = rest.split(';').map(CubeSet::create).collect();
+
+        CubeGame { id, rounds }
+    }
+
+    pub fn rounds(&self) -> &[CubeSet] {
+        &self.rounds
+    }
+
+    /// The most cubes of each colour shown in any single round.
+    pub fn max_counts(&self) -> CubeSet {
+        let mut max_counts = CubeSet::default();
+
+        for round in self.rounds.iter() {
+            for (color, count) in round.iter() {
+                max_counts.include_max(color, count);
+            }
+        }
+
+        max_counts
+    }
+
+    pub fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.first_impossible_round(bag).is_none()
+    }
+
+    /// The index of the first round that shows more cubes of some colour
+    /// than `bag` holds, if any.
+    pub fn first_impossible_round(&self, bag: &Bag) -> Option<usize> {
+        self.rounds.iter().position(|round| !round.fits_in(bag))
+    }
+
+    pub fn round_totals(&self) -> Vec<u32> {
+        self.rounds.iter().map(CubeSet::total).collect()
+    }
+
+    /// Groups of round indices that showed exactly the same cubes. Rounds
+    /// that only happened once are left out.
+    pub fn repeated_rounds(&self) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<&CubeSet, Vec<usize>> = HashMap::new();
+        for (i, round) in self.rounds.iter().enumerate() {
+            groups.entry(round).or_default().push(i);
+        }
+
+        let mut repeated = groups
+            .into_values()
+            .filter(|indices| indices.len() > 1)
+            .collect::<Vec<_>>();
+
+        repeated.sort();
+        repeated
+    }
+
+    /// The smallest bag that could have produced every round of this game.
+    pub fn minimal_bag(&self) -> Bag {
+        Bag(self.max_counts())
+    }
+
+    pub fn into_power(self) -> u32 {
+        self.max_counts().iter().map(|(_, count)| count).product()
+    }
+}
+
+impl CubeSet {
+    /// Parses a comma separated list of draws, e.g. `3 blue, 4 red`.
+    pub fn create(input: &str) -> CubeSet {
+        let mut set = CubeSet::default();
+
+        for color_count in input.split(',') {
+            let parts = color_count.trim().split(' ').collect::<Vec<_>>();
+            let num: u32 = parts.first().unwrap().trim().parse().unwrap();
+            let color = parts.last().unwrap();
+
+            *set.0.entry(color.to_string()).or_insert(0) += num;
+        }
+
+        set
+    }
+
+    pub fn count(&self, color: &str) -> u32 {
+        self.0.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.0.iter().map(|(color, &count)| (color.as_str(), count))
+    }
+
+    pub fn total(&self) -> u32 {
+        self.0.values().sum()
+    }
+
+    pub fn fits_in(&self, bag: &Bag) -> bool {
+        self.iter().all(|(color, count)| count <= bag.count(color))
+    }
+
+    fn include_max(&mut self, color: &str, count: u32) {
+        let max = self.0.entry(color.to_string()).or_insert(0);
+        if *max < count {
+            *max = count;
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, u32)> for CubeSet {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(iter: T) -> Self {
+        CubeSet(
+            iter.into_iter()
+                .map(|(color, count)| (color.into(), count))
+                .collect(),
+        )
+    }
+}
+
+impl From<CubeSet> for Bag {
+    fn from(cubes: CubeSet) -> Self {
+        Bag(cubes)
+    }
+}
+
+impl Bag {
+    /// Parses a bag specification in the flat `bag.toml` format:
+    ///
+    /// ```toml
+    /// [bag]
+    /// red = 12
+    /// green = 13
+    /// blue = 14
+    /// ```
+    ///
+    /// Blank lines, `#` comments and table headers are ignored.
+    pub fn create(input: &str) -> Bag {
+        let mut set = CubeSet::default();
+
+        for line in input.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+
+            let (color, count) = line.split_once('=').expect("Expected `color = count`");
+            let color = color.trim().trim_matches('"');
+            let count = count.trim().parse().expect("Expected a cube count");
+
+            set.0.insert(color.to_string(), count);
+        }
+
+        Bag(set)
+    }
+
+    /// Reads the bag from `filename`, falling back to [`Bag::standard`] if the
+    /// file doesn't exist.
+    pub fn load(filename: impl AsRef<Path>) -> Bag {
+        match fs::read_to_string(filename) {
+            Ok(input) => Bag::create(&input),
+            Err(_) => Bag::standard(),
+        }
+    }
+
+    /// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn standard() -> Bag {
+        Bag::create("red = 12\ngreen = 13\nblue = 14")
+    }
+
+    pub fn count(&self, color: &str) -> u32 {
+        self.0.count(color)
+    }
+
+    pub fn cubes(&self) -> &CubeSet {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    pub fn example_games() -> Vec<CubeGame> {
+        EXAMPLE.lines().map(CubeGame::create).collect()
+    }
+
+    #[test]
+    fn example() {
+        let bag = Bag::standard();
+        let games = example_games();
+
+        let possible_ids = games
+            .iter()
+            .filter(|game| game.is_possible_with(&bag))
+            .map(|game| game.id)
+            .sum::<u32>();
+        assert_eq!(possible_ids, 8);
+
+        let powers = games.into_iter().map(CubeGame::into_power).sum::<u32>();
+        assert_eq!(powers, 2286);
+    }
+
+    #[test]
+    fn first_impossible_round() {
+        let bag = Bag::standard();
+        let games = example_games();
+
+        let rounds = games
+            .iter()
+            .map(|game| game.first_impossible_round(&bag))
+            .collect::<Vec<_>>();
+        assert_eq!(rounds, [None, None, Some(0), Some(2), None]);
+    }
+
+    #[test]
+    fn repeated_rounds() {
+        let game =
+            CubeGame::create("Game 7: 1 red, 2 blue; 3 green; 2 blue, 1 red; 3 green; 1 red");
+        assert_eq!(game.repeated_rounds(), [vec![0, 2], vec![1, 3]]);
+
+        let game = CubeGame::create("Game 8: 1 red; 2 red");
+        assert!(game.repeated_rounds().is_empty());
+    }
+}
@@ -1,19 +1,47 @@
-use std::fs;
+use std::{env, fs, process};
+
+use day_2::{query::Query, Bag, CubeGame};
 
 fn main() {
-    let part_1_answer = find_id_sum_of_possible_games("input.txt");
+    let bag = Bag::load("bag.toml");
+
+    let mut filter = None;
+    let mut select = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--where" => filter = args.next(),
+            "--select" => select = args.next(),
+            _ => usage(),
+        }
+    }
+
+    if filter.is_some() || select.is_some() {
+        let select = select.as_deref().unwrap_or("sum(id)");
+        let answer = run_query("input.txt", &bag, filter.as_deref(), select);
+        println!("{select}: {answer}");
+        return;
+    }
+
+    let part_1_answer = find_id_sum_of_possible_games("input.txt", &bag);
     println!("Part 1 Answer: {part_1_answer}");
 
     let part_2_answer = find_power_sum_of_all_games("input.txt");
     println!("Part 2 Answer: {part_2_answer}");
 }
 
-fn find_id_sum_of_possible_games(filename: &str) -> u32 {
+fn usage() -> ! {
+    eprintln!("Usage: day_2 [--where <condition>] [--select <sum(...)|min(...)|max(...)|count>]");
+    process::exit(2);
+}
+
+fn find_id_sum_of_possible_games(filename: &str, bag: &Bag) -> u32 {
     fs::read_to_string(filename)
         .expect("Couldn't find input file")
         .lines()
         .map(CubeGame::create)
-        .filter(|game| game.is_possible_with(12, 13, 14))
+        .filter(|game| game.is_possible_with(bag))
         .map(|game| game.id)
         .sum()
 }
@@ -27,66 +55,16 @@ fn find_power_sum_of_all_games(filename: &str) -> u32 {
         .sum()
 }
 
-struct CubeGame {
-    id: u32,
-    max_red: u32,
-    max_green: u32,
-    max_blue: u32,
-}
-
-impl CubeGame {
-    fn create(input: &str) -> CubeGame {
-        let parts = input.split(":").collect::<Vec<_>>();
-        let id_part = parts.first().unwrap();
-        let rest = parts.last().unwrap();
-
-        let id = id_part.trim_start_matches("Game ").parse().unwrap();
-        let mut max_red = 0;
-        let mut max_green = 0;
-        let mut max_blue = 0;
-
-        let parts = rest.split(";");
-        for part in parts {
-            let color_counts = part.split(",");
-            for color_count in color_counts {
-                let parts = color_count.trim().split(" ").collect::<Vec<_>>();
-                let num = parts.first().unwrap().trim().parse().unwrap();
-                let color = parts.last().unwrap();
-
-                match color {
-                    &"red" => {
-                        if max_red < num {
-                            max_red = num;
-                        }
-                    }
-                    &"green" => {
-                        if max_green < num {
-                            max_green = num;
-                        }
-                    }
-                    &"blue" => {
-                        if max_blue < num {
-                            max_blue = num;
-                        }
-                    }
-                    _ => (),
-                };
-            }
-        }
-
-        CubeGame {
-            id,
-            max_red,
-            max_green,
-            max_blue,
-        }
-    }
-
-    fn is_possible_with(&self, red: u32, green: u32, blue: u32) -> bool {
-        self.max_red <= red && self.max_green <= green && self.max_blue <= blue
-    }
+fn run_query(filename: &str, bag: &Bag, filter: Option<&str>, select: &str) -> i64 {
+    let games = fs::read_to_string(filename)
+        .expect("Couldn't find input file")
+        .lines()
+        .map(CubeGame::create)
+        .collect::<Vec<_>>();
 
-    fn into_power(self) -> u32 {
-        self.max_red * self.max_green * self.max_blue
-    }
+    let result = Query::parse(filter, select).and_then(|query| query.run(&games, bag));
+    result.unwrap_or_else(|err| {
+        eprintln!("Invalid query: {err}");
+        process::exit(2);
+    })
 }
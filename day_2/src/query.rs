@@ -0,0 +1,624 @@
+//! A small expression language for asking ad-hoc questions about games.
+//!
+//! A query is a `--where` filter and a `--select` aggregate, e.g.
+//!
+//! ```text
+//! --where 'max(red) <= 12 && any_round(blue > 3)' --select 'sum(power)'
+//! ```
+//!
+//! Expressions are evaluated either against a whole game or against a single
+//! round of it. At game level the following are available:
+//!
+//! - `id`, `power`, `rounds` (the number of rounds) and `total` (every cube
+//!   shown across all rounds)
+//! - `possible`, which checks the game against the configured bag
+//! - `max(e)`, `min(e)` and `sum(e)`, which evaluate `e` for every round
+//! - `any_round(p)`, `all_rounds(p)` and `count_rounds(p)`, likewise
+//! - any other name is a colour, and means the most of it seen in one round.
+//!   Names that aren't a colour in any of the games or in the bag are
+//!   rejected, so that a typo doesn't quietly count as 0
+//!
+//! Inside a round, a colour name is that round's count of it, `total` is every
+//! cube shown in the round and `id` is still the game's id.
+//!
+//! Numbers combine with `+`, `-` and `*` (overflowing is an error), compare with `<`, `<=`, `>`, `>=`,
+//! `==` and `!=` (`≤`, `≥` and `≠` also work), and conditions combine with
+//! `!`, `&&` and `||`.
+//!
+//! A select is `sum(e)`, `min(e)`, `max(e)` or `count`, evaluated over every
+//! game that passes the filter.
+//!
+//! `sum` of nothing is 0, but `min` and `max` of nothing (no games passing the
+//! filter, or a game with no rounds) is an error.
+
+use std::{collections::BTreeSet, fmt, iter::Peekable, str::Chars};
+
+use crate::{Bag, CubeGame, CubeSet};
+
+#[derive(Debug)]
+pub struct Query {
+    filter: Option<Expr>,
+    select: Select,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError(String);
+
+#[derive(Debug)]
+enum Select {
+    Sum(Expr),
+    Min(Expr),
+    Max(Expr),
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(i64),
+    Name(String),
+    Call(String, Box<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(i64),
+    Name(String),
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Compare(CompareOp),
+    Arith(ArithOp),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Value {
+    Num(i64),
+    Bool(bool),
+}
+
+#[derive(Copy, Clone)]
+enum Scope<'a> {
+    Game(&'a CubeGame),
+    Round(&'a CubeGame, &'a CubeSet),
+}
+
+impl Query {
+    /// Parses a query from its `--where` filter (if any) and `--select`
+    /// aggregate.
+    pub fn parse(filter: Option<&str>, select: &str) -> Result<Self, QueryError> {
+        let filter = filter.map(Expr::parse).transpose()?;
+        let select = Select::parse(select)?;
+
+        Ok(Self { filter, select })
+    }
+
+    /// The games that pass the filter, in order.
+    pub fn filter<'a>(
+        &self,
+        games: &'a [CubeGame],
+        bag: &Bag,
+    ) -> Result<Vec<&'a CubeGame>, QueryError> {
+        if let Some(ref filter) = self.filter {
+            filter.check_names(&known_colors(games, bag))?;
+        }
+
+        let mut matches = vec![];
+
+        for game in games {
+            let keep = match self.filter {
+                Some(ref filter) => filter.eval(Scope::Game(game), bag)?.as_bool()?,
+                None => true,
+            };
+
+            if keep {
+                matches.push(game);
+            }
+        }
+
+        Ok(matches)
+    }
+
+    pub fn run(&self, games: &[CubeGame], bag: &Bag) -> Result<i64, QueryError> {
+        if let Select::Sum(expr) | Select::Min(expr) | Select::Max(expr) = &self.select {
+            expr.check_names(&known_colors(games, bag))?;
+        }
+
+        let games = self.filter(games, bag)?;
+        self.select.eval(&games, bag)
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Select {
+    fn parse(input: &str) -> Result<Self, QueryError> {
+        match Expr::parse(input)? {
+            Expr::Name(name) if name == "count" => Ok(Select::Count),
+            Expr::Call(name, arg) => match name.as_str() {
+                "sum" => Ok(Select::Sum(*arg)),
+                "min" => Ok(Select::Min(*arg)),
+                "max" => Ok(Select::Max(*arg)),
+                _ => Err(error(format!(
+                    "`{name}` can't be selected, expected sum, min, max or count"
+                ))),
+            },
+            _ => Err(error("Expected sum(...), min(...), max(...) or count")),
+        }
+    }
+
+    fn eval(&self, games: &[&CubeGame], bag: &Bag) -> Result<i64, QueryError> {
+        let (expr, fold, empty): (_, fn(i64, i64) -> Option<i64>, _) = match self {
+            Select::Count => return Ok(games.len() as i64),
+            Select::Sum(expr) => (expr, i64::checked_add, Some(0)),
+            Select::Min(expr) => (expr, |a, b| Some(a.min(b)), None),
+            Select::Max(expr) => (expr, |a, b| Some(a.max(b)), None),
+        };
+
+        let values = games.iter().map(|game| expr.eval(Scope::Game(game), bag));
+        let result = fold_nums(values, fold, empty)?;
+        result.ok_or_else(|| error("No games match, so there's nothing to take the min or max of"))
+    }
+}
+
+impl Expr {
+    fn parse(input: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            Some(token) => Err(error(format!(
+                "Unexpected {token:?} after the end of the expression"
+            ))),
+            None => Ok(expr),
+        }
+    }
+
+    fn eval(&self, scope: Scope, bag: &Bag) -> Result<Value, QueryError> {
+        match self {
+            Expr::Num(num) => Ok(Value::Num(*num)),
+            Expr::Name(name) => scope.lookup(name, bag),
+            Expr::Call(name, arg) => {
+                let Scope::Game(game) = scope else {
+                    return Err(error(format!(
+                        "`{name}(...)` can only be used on a whole game"
+                    )));
+                };
+
+                let mut values = game
+                    .rounds()
+                    .iter()
+                    .map(|round| arg.eval(Scope::Round(game, round), bag));
+
+                let no_rounds = || {
+                    error(format!(
+                        "`{name}(...)` needs a game with at least one round"
+                    ))
+                };
+
+                match name.as_str() {
+                    "max" => fold_nums(values, |a, b| Some(a.max(b)), None)?
+                        .map(Value::Num)
+                        .ok_or_else(no_rounds),
+                    "min" => fold_nums(values, |a, b| Some(a.min(b)), None)?
+                        .map(Value::Num)
+                        .ok_or_else(no_rounds),
+                    "sum" => fold_nums(values, i64::checked_add, Some(0))?
+                        .map(Value::Num)
+                        .ok_or_else(no_rounds),
+                    "any_round" => values
+                        .try_fold(false, |any, value| Ok(any || value?.as_bool()?))
+                        .map(Value::Bool),
+                    "all_rounds" => values
+                        .try_fold(true, |all, value| Ok(all && value?.as_bool()?))
+                        .map(Value::Bool),
+                    "count_rounds" => values
+                        .try_fold(0, |count, value| Ok(count + value?.as_bool()? as i64))
+                        .map(Value::Num),
+                    _ => Err(error(format!("Unknown function `{name}`"))),
+                }
+            }
+            Expr::Not(expr) => Ok(Value::Bool(!expr.eval(scope, bag)?.as_bool()?)),
+            Expr::And(lhs, rhs) => Ok(Value::Bool(
+                lhs.eval(scope, bag)?.as_bool()? && rhs.eval(scope, bag)?.as_bool()?,
+            )),
+            Expr::Or(lhs, rhs) => Ok(Value::Bool(
+                lhs.eval(scope, bag)?.as_bool()? || rhs.eval(scope, bag)?.as_bool()?,
+            )),
+            Expr::Compare(op, lhs, rhs) => {
+                let lhs = lhs.eval(scope, bag)?.as_num()?;
+                let rhs = rhs.eval(scope, bag)?.as_num()?;
+
+                Ok(Value::Bool(match op {
+                    CompareOp::Lt => lhs < rhs,
+                    CompareOp::Le => lhs <= rhs,
+                    CompareOp::Gt => lhs > rhs,
+                    CompareOp::Ge => lhs >= rhs,
+                    CompareOp::Eq => lhs == rhs,
+                    CompareOp::Ne => lhs != rhs,
+                }))
+            }
+            Expr::Arith(op, lhs, rhs) => {
+                let lhs = lhs.eval(scope, bag)?.as_num()?;
+                let rhs = rhs.eval(scope, bag)?.as_num()?;
+
+                let result = match op {
+                    ArithOp::Add => lhs.checked_add(rhs),
+                    ArithOp::Sub => lhs.checked_sub(rhs),
+                    ArithOp::Mul => lhs.checked_mul(rhs),
+                };
+
+                result.map(Value::Num).ok_or_else(overflow)
+            }
+        }
+    }
+
+    /// Rejects any name that isn't built in or one of `colors`.
+    fn check_names(&self, colors: &BTreeSet<&str>) -> Result<(), QueryError> {
+        match self {
+            Expr::Num(_) => Ok(()),
+            Expr::Name(name) => match name.as_str() {
+                "id" | "power" | "rounds" | "total" | "possible" => Ok(()),
+                color if colors.contains(color) => Ok(()),
+                _ => Err(error(format!(
+                    "Unknown name `{name}`, expected a colour or a built-in"
+                ))),
+            },
+            Expr::Call(_, arg) | Expr::Not(arg) => arg.check_names(colors),
+            Expr::And(lhs, rhs)
+            | Expr::Or(lhs, rhs)
+            | Expr::Compare(_, lhs, rhs)
+            | Expr::Arith(_, lhs, rhs) => {
+                lhs.check_names(colors)?;
+                rhs.check_names(colors)
+            }
+        }
+    }
+}
+
+impl Scope<'_> {
+    fn lookup(&self, name: &str, bag: &Bag) -> Result<Value, QueryError> {
+        let value = match (self, name) {
+            (Scope::Game(game) | Scope::Round(game, _), "id") => Value::Num(game.id as i64),
+            (Scope::Game(game), "power") => {
+                let power = game
+                    .max_counts()
+                    .iter()
+                    .try_fold(1i64, |power, (_, count)| power.checked_mul(count as i64));
+
+                Value::Num(power.ok_or_else(overflow)?)
+            }
+            (Scope::Game(game), "rounds") => Value::Num(game.rounds().len() as i64),
+            (Scope::Game(game), "total") => {
+                Value::Num(game.round_totals().iter().map(|&total| total as i64).sum())
+            }
+            (Scope::Game(game), "possible") => Value::Bool(game.is_possible_with(bag)),
+            (Scope::Game(game), color) => Value::Num(game.max_counts().count(color) as i64),
+            (Scope::Round(_, round), "total") => Value::Num(round.total() as i64),
+            (Scope::Round(..), "power" | "rounds" | "possible") => {
+                return Err(error(format!("`{name}` can only be used on a whole game")));
+            }
+            (Scope::Round(_, round), color) => Value::Num(round.count(color) as i64),
+        };
+
+        Ok(value)
+    }
+}
+
+impl Value {
+    fn as_num(self) -> Result<i64, QueryError> {
+        match self {
+            Value::Num(num) => Ok(num),
+            Value::Bool(_) => Err(error("Expected a number but found a condition")),
+        }
+    }
+
+    fn as_bool(self) -> Result<bool, QueryError> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            Value::Num(_) => Err(error("Expected a condition but found a number")),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.parse_and()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_not()?;
+        while self.eat(&Token::And) {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.parse_not()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, QueryError> {
+        if self.eat(&Token::Not) {
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_compare()
+        }
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, QueryError> {
+        let lhs = self.parse_sum()?;
+
+        match self.tokens.get(self.pos) {
+            Some(&Token::Compare(op)) => {
+                self.pos += 1;
+                Ok(Expr::Compare(
+                    op,
+                    Box::new(lhs),
+                    Box::new(self.parse_sum()?),
+                ))
+            }
+            _ => Ok(lhs),
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_product()?;
+
+        while let Some(&Token::Arith(op @ (ArithOp::Add | ArithOp::Sub))) =
+            self.tokens.get(self.pos)
+        {
+            self.pos += 1;
+            lhs = Expr::Arith(op, Box::new(lhs), Box::new(self.parse_product()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_product(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_atom()?;
+        while self.eat(&Token::Arith(ArithOp::Mul)) {
+            lhs = Expr::Arith(ArithOp::Mul, Box::new(lhs), Box::new(self.parse_atom()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr, QueryError> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+
+        match token {
+            Some(Token::Num(num)) => Ok(Expr::Num(num)),
+            Some(Token::Name(name)) => {
+                if !self.eat(&Token::LParen) {
+                    return Ok(Expr::Name(name));
+                }
+
+                let arg = self.parse_or()?;
+                self.expect(Token::RParen)?;
+
+                Ok(Expr::Call(name, Box::new(arg)))
+            }
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+
+                Ok(expr)
+            }
+            Some(token) => Err(error(format!("Unexpected {token:?}"))),
+            None => Err(error("Unexpected end of expression")),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), QueryError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(error(format!("Expected {token:?}")))
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' => {
+                let mut digits = c.to_string();
+                take_while(&mut chars, &mut digits, |c| c.is_ascii_digit());
+
+                Token::Num(
+                    digits
+                        .parse()
+                        .map_err(|_| error(format!("`{digits}` is too large")))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                take_while(&mut chars, &mut name, |c| c.is_alphanumeric() || c == '_');
+
+                Token::Name(name)
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '+' => Token::Arith(ArithOp::Add),
+            '-' => Token::Arith(ArithOp::Sub),
+            '*' => Token::Arith(ArithOp::Mul),
+            '≤' => Token::Compare(CompareOp::Le),
+            '≥' => Token::Compare(CompareOp::Ge),
+            '≠' => Token::Compare(CompareOp::Ne),
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Compare(CompareOp::Le),
+            '<' => Token::Compare(CompareOp::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Compare(CompareOp::Ge),
+            '>' => Token::Compare(CompareOp::Gt),
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Compare(CompareOp::Eq),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Compare(CompareOp::Ne),
+            '!' => Token::Not,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            c => return Err(error(format!("Unexpected character `{c}`"))),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn take_while(chars: &mut Peekable<Chars>, buf: &mut String, predicate: impl Fn(char) -> bool) {
+    while let Some(c) = chars.next_if(|&c| predicate(c)) {
+        buf.push(c);
+    }
+}
+
+/// Folds `values` together, or gives `empty` if there aren't any.
+fn fold_nums(
+    values: impl Iterator<Item = Result<Value, QueryError>>,
+    fold: impl Fn(i64, i64) -> Option<i64>,
+    empty: Option<i64>,
+) -> Result<Option<i64>, QueryError> {
+    let mut result = None;
+    for value in values {
+        let value = value?.as_num()?;
+        result = Some(match result {
+            Some(result) => fold(result, value).ok_or_else(overflow)?,
+            None => value,
+        });
+    }
+
+    Ok(result.or(empty))
+}
+
+/// Every colour shown in any of `games` or held in `bag`.
+fn known_colors<'a>(games: &'a [CubeGame], bag: &'a Bag) -> BTreeSet<&'a str> {
+    let shown = games
+        .iter()
+        .flat_map(|game| game.rounds())
+        .flat_map(|round| round.iter());
+
+    shown
+        .chain(bag.cubes().iter())
+        .map(|(color, _)| color)
+        .collect()
+}
+
+fn overflow() -> QueryError {
+    error("The query overflowed a 64-bit number")
+}
+
+fn error(message: impl Into<String>) -> QueryError {
+    QueryError(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_games;
+
+    fn run(filter: Option<&str>, select: &str) -> Result<i64, QueryError> {
+        Query::parse(filter, select)?.run(&example_games(), &Bag::standard())
+    }
+
+    fn run_err(filter: Option<&str>, select: &str) -> String {
+        run(filter, select).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn filter_and_select() {
+        let filter = Some("max(red) <= 12 && any_round(blue > 3)");
+        assert_eq!(run(filter, "sum(id)"), Ok(3));
+        assert_eq!(run(filter, "count"), Ok(2));
+
+        assert_eq!(run(Some("possible"), "sum(id)"), Ok(8));
+        assert_eq!(run(None, "sum(power)"), Ok(2286));
+        assert_eq!(run(None, "max(rounds)"), Ok(3));
+        assert_eq!(run(Some("count_rounds(total >= 10) > 1"), "min(id)"), Ok(3));
+    }
+
+    #[test]
+    fn unicode_comparisons() {
+        assert_eq!(
+            run(Some("max(red) ≤ 12 && max(blue) ≥ 6"), "sum(id)"),
+            Ok(1)
+        );
+        assert_eq!(
+            run(Some("max(red) <= 12 && max(blue) >= 6"), "sum(id)"),
+            Ok(1)
+        );
+        assert_eq!(run(Some("id ≠ 3"), "count"), run(Some("id != 3"), "count"));
+    }
+
+    #[test]
+    fn unknown_names_and_functions() {
+        assert!(run_err(Some("rde > 0"), "count").contains("Unknown name `rde`"));
+        assert!(run_err(None, "sum(bleu)").contains("Unknown name `bleu`"));
+        assert!(run_err(Some("foo(red) > 0"), "count").contains("Unknown function `foo`"));
+    }
+
+    #[test]
+    fn overflow() {
+        let err = run_err(Some("id * 9999999999 * 9999999999 > 0"), "count");
+        assert!(err.contains("overflowed"));
+
+        let err = run_err(None, "sum(id * 9223372036854775807)");
+        assert!(err.contains("overflowed"));
+    }
+
+    #[test]
+    fn min_and_max_of_nothing() {
+        assert_eq!(run(Some("red > 100"), "sum(id)"), Ok(0));
+        assert_eq!(run(Some("red > 100"), "count"), Ok(0));
+        assert!(run_err(Some("red > 100"), "min(id)").contains("No games match"));
+        assert!(run_err(Some("red > 100"), "max(id)").contains("No games match"));
+    }
+}
@@ -0,0 +1,330 @@
+//! Editing an already parsed schematic one cell at a time. Only the numbers
+//! and symbols around the edited cell are re-scanned, so an edit costs the
+//! same however large the schematic is.
+
+use std::{collections::BTreeSet, iter, ops::Range};
+
+use crate::{find_numbers, rules::Neighbourhood, PartData, Position, SchematicError};
+
+impl PartData {
+    /// Replaces the cell at `row`, `col` with `ch`, then updates the numbers,
+    /// symbols and both sums to match. Fails without changing anything if the
+    /// edit would join digits into a number that doesn't fit in a `u32`.
+    ///
+    /// Panics if the cell is outside the schematic.
+    pub fn set_cell(&mut self, row: usize, col: usize, ch: char) -> Result<(), SchematicError> {
+        let pos = Position { row, col };
+        let old = self
+            .grid
+            .get(row, col)
+            .unwrap_or_else(|| panic!("({row}, {col}) is outside the schematic"));
+
+        if old == ch {
+            return Ok(());
+        }
+
+        if ch.is_ascii_digit() {
+            self.check_joined_number(row, col, ch)?;
+        }
+
+        // Every cell whose number or symbol could be affected by the edit.
+        // This is deliberately the 8-connected neighbourhood whatever the
+        // rules say, since it covers the 4-connected one.
+        let cells = iter::once((row, col))
+            .chain(
+                self.grid
+                    .neighbours(row, col..col + 1, Neighbourhood::Eight),
+            )
+            .map(|(row, col)| Position { row, col })
+            .collect::<Vec<_>>();
+
+        let mut numbers = BTreeSet::new();
+        let mut symbols = BTreeSet::new();
+
+        for cell in cells.iter() {
+            if let Some(&number) = self.number_map.get(cell) {
+                let number = &self.numbers[number];
+                numbers.insert((number.row, number.col_span.start, number.col_span.end));
+            }
+
+            if self.symbol_map.contains_key(cell) {
+                symbols.insert(*cell);
+            }
+        }
+
+        for &(row, start, _) in numbers.iter() {
+            let number = &self.numbers[self.number_map[&Position { row, col: start }]];
+            symbols.extend(
+                number
+                    .adjacent_symbols
+                    .iter()
+                    .map(|&symbol| self.symbols[symbol].pos),
+            );
+        }
+
+        // Take out everything that's about to change...
+        for symbol in symbols.iter() {
+            if let Some(ratio) = self.gear_ratio(self.symbol_map[symbol]) {
+                self.gear_ratio_sum.sub(ratio);
+            }
+        }
+
+        for &(row, start, _) in numbers.iter() {
+            let index = self.number_map[&Position { row, col: start }];
+            if self.numbers[index].is_part_number() {
+                self.part_number_sum -= u64::from(self.numbers[index].value);
+            }
+
+            self.remove_number(index);
+        }
+
+        if let Some(&index) = self.symbol_map.get(&pos) {
+            self.remove_symbol(index);
+        }
+
+        // ...make the edit...
+        self.grid.set(row, col, ch);
+        self.add_symbol(pos);
+        if self.symbol_map.contains_key(&pos) {
+            symbols.insert(pos);
+        }
+
+        // ...and put it all back. Numbers on other rows haven't changed, but
+        // on the edited row they may have been split or joined together.
+        let mut windows = numbers
+            .iter()
+            .filter(|&&(number_row, _, _)| number_row != row)
+            .map(|&(row, start, end)| (row, start..end))
+            .collect::<Vec<_>>();
+
+        let start = numbers
+            .iter()
+            .filter(|&&(number_row, _, _)| number_row == row)
+            .map(|&(_, start, _)| start)
+            .fold(col, usize::min);
+        let end = numbers
+            .iter()
+            .filter(|&&(number_row, _, _)| number_row == row)
+            .map(|&(_, _, end)| end)
+            .fold(col + 1, usize::max);
+        windows.push((row, start..end));
+
+        for (row, window) in windows {
+            self.rescan(row, window);
+        }
+
+        for symbol in symbols.iter() {
+            if let Some(&index) = self.symbol_map.get(symbol) {
+                if let Some(ratio) = self.gear_ratio(index) {
+                    self.gear_ratio_sum.add(ratio);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the run of digits through `col` still fits in a `u32`
+    /// once that cell holds the digit `ch`.
+    fn check_joined_number(&self, row: usize, col: usize, ch: char) -> Result<(), SchematicError> {
+        let cells = self.grid.row(row);
+        let start = cells[..col]
+            .iter()
+            .rposition(|c| !c.is_ascii_digit())
+            .map_or(0, |i| i + 1);
+        let end = cells[col + 1..]
+            .iter()
+            .position(|c| !c.is_ascii_digit())
+            .map_or(cells.len(), |i| col + 1 + i);
+
+        let digits = cells[start..col]
+            .iter()
+            .chain(Some(&ch))
+            .chain(cells[col + 1..end].iter())
+            .collect::<String>();
+
+        match digits.parse::<u32>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(SchematicError::NumberTooLarge(Position { row, col: start })),
+        }
+    }
+
+    /// Adds every number found in `cols` of `row`.
+    fn rescan(&mut self, row: usize, cols: Range<usize>) {
+        let found = find_numbers(&self.grid.row(row)[cols.clone()])
+            .expect("Expected edits to have been checked for numbers that are too large");
+
+        for (span, value) in found {
+            let span = span.start + cols.start..span.end + cols.start;
+            let index = self.add_number(row, span, value);
+
+            if self.numbers[index].is_part_number() {
+                self.part_number_sum += u64::from(value);
+            }
+        }
+    }
+
+    /// Removes the number at `index`, unlinking it from its symbols. The last
+    /// number is moved into its place.
+    fn remove_number(&mut self, index: usize) {
+        let number = self.numbers.swap_remove(index);
+
+        for &symbol in number.adjacent_symbols.iter() {
+            self.symbols[symbol]
+                .adjacent_parts
+                .retain(|&part| part != index);
+        }
+
+        for col in number.col_span {
+            self.number_map.remove(&Position {
+                row: number.row,
+                col,
+            });
+        }
+
+        let moved = self.numbers.len();
+        if index == moved {
+            return;
+        }
+
+        let number = &self.numbers[index];
+        for col in number.col_span.clone() {
+            self.number_map.insert(
+                Position {
+                    row: number.row,
+                    col,
+                },
+                index,
+            );
+        }
+
+        for &symbol in number.adjacent_symbols.iter() {
+            for part in self.symbols[symbol].adjacent_parts.iter_mut() {
+                if *part == moved {
+                    *part = index;
+                }
+            }
+        }
+    }
+
+    /// Removes the symbol at `index`, unlinking it from its numbers. The last
+    /// symbol is moved into its place.
+    fn remove_symbol(&mut self, index: usize) {
+        let symbol = self.symbols.swap_remove(index);
+
+        for &part in symbol.adjacent_parts.iter() {
+            self.numbers[part]
+                .adjacent_symbols
+                .retain(|&symbol| symbol != index);
+        }
+
+        self.symbol_map.remove(&symbol.pos);
+
+        let moved = self.symbols.len();
+        if index == moved {
+            return;
+        }
+
+        let symbol = &self.symbols[index];
+        self.symbol_map.insert(symbol.pos, index);
+
+        for &part in symbol.adjacent_parts.iter() {
+            for symbol in self.numbers[part].adjacent_symbols.iter_mut() {
+                if *symbol == moved {
+                    *symbol = index;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PartData, Position};
+
+    /// Every number with the positions of the symbols it touches, which
+    /// doesn't depend on the order things were added in.
+    fn summary(part_data: &PartData) -> Vec<(usize, usize, u32, Vec<Position>)> {
+        let mut numbers = part_data
+            .numbers()
+            .iter()
+            .map(|number| {
+                let mut symbols = number
+                    .adjacent_symbols
+                    .iter()
+                    .map(|&symbol| part_data.symbols()[symbol].pos)
+                    .collect::<Vec<_>>();
+                symbols.sort();
+
+                (number.row, number.col_span.start, number.value, symbols)
+            })
+            .collect::<Vec<_>>();
+
+        numbers.sort();
+        numbers
+    }
+
+    #[test]
+    fn edits_match_parsing_from_scratch() {
+        let mut cells = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598.."
+            .lines()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut part_data = PartData::create(&to_text(&cells)).unwrap();
+        let choices = ['.', '.', '*', '#', '1', '5', '9'];
+
+        // A small xorshift, so that the edits are the same every run.
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize % bound
+        };
+
+        for _ in 0..500 {
+            let row = next(cells.len());
+            let col = next(cells[row].len());
+            let ch = choices[next(choices.len())];
+
+            let old = cells[row][col];
+            cells[row][col] = ch;
+
+            // Edits that make a number too large are turned down by both.
+            if part_data.set_cell(row, col, ch).is_err() {
+                assert!(PartData::create(&to_text(&cells)).is_err());
+                cells[row][col] = old;
+            }
+
+            let expected = PartData::create(&to_text(&cells)).unwrap();
+            assert_eq!(summary(&part_data), summary(&expected));
+            assert_eq!(
+                part_data.get_part_number_sum(),
+                expected.get_part_number_sum()
+            );
+            assert_eq!(
+                part_data.get_gear_ratio_sum(),
+                expected.get_gear_ratio_sum()
+            );
+        }
+    }
+
+    fn to_text(cells: &[Vec<char>]) -> String {
+        cells
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+use crate::rules::Neighbourhood;
+
+/// A rectangular grid of characters, stored row by row in a single `Vec` so
+/// that any cell can be looked up in constant time.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    cells: Vec<char>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid {
+    /// Builds a grid from the lines of `input`. Lines shorter than the
+    /// longest one are padded out with `fill`.
+    pub fn create(input: &str, fill: char) -> Self {
+        let lines = input.lines().collect::<Vec<_>>();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = lines.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for line in lines {
+            let len = cells.len();
+            cells.extend(line.chars());
+            cells.resize(len + width, fill);
+        }
+
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<char> {
+        if row < self.height && col < self.width {
+            Some(self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, c: char) {
+        assert!(
+            row < self.height && col < self.width,
+            "({row}, {col}) is outside the grid"
+        );
+        self.cells[row * self.width + col] = c;
+    }
+
+    pub fn row(&self, row: usize) -> &[char] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Every in-bounds cell touching the horizontal run `cols` on `row`.
+    /// Each cell is yielded once, as `(row, col)`.
+    pub fn neighbours(
+        &self,
+        row: usize,
+        cols: Range<usize>,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let first_col = cols.start.saturating_sub(1);
+        let last_col = (cols.end + 1).min(self.width);
+        let first_row = row.saturating_sub(1);
+        let last_row = (row + 2).min(self.height);
+
+        (first_row..last_row)
+            .flat_map(move |r| (first_col..last_col).map(move |c| (r, c)))
+            .filter(move |&(r, c)| match neighbourhood {
+                Neighbourhood::Eight => r != row || !cols.contains(&c),
+                Neighbourhood::Four => (r == row) != cols.contains(&c),
+            })
+    }
+}
@@ -0,0 +1,477 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    ops::Range,
+};
+
+mod edit;
+mod grid;
+use grid::Grid;
+
+pub mod machines;
+pub mod render;
+pub mod rules;
+use rules::Rules;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub row: usize,
+    pub col: usize,
+}
+
+/// A run of digits in the schematic. `adjacent_symbols` holds indices into
+/// [`PartData::symbols`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u32,
+    pub row: usize,
+    pub col_span: Range<usize>,
+    pub adjacent_symbols: Vec<usize>,
+}
+
+/// Any character that the [`Rules`] count as a symbol. `adjacent_parts` holds
+/// indices into [`PartData::numbers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub ch: char,
+    pub pos: Position,
+    pub adjacent_parts: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchematicError {
+    /// The number starting at this position doesn't fit in a `u32`.
+    NumberTooLarge(Position),
+}
+
+#[derive(Debug)]
+pub struct PartData {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    rules: Rules,
+    grid: Grid,
+    number_map: HashMap<Position, usize>,
+    symbol_map: HashMap<Position, usize>,
+    /// Every number is a `u32`, so this would need more than 2^32 of them
+    /// to overflow.
+    part_number_sum: u64,
+    gear_ratio_sum: Total,
+}
+
+/// A running total of `u128`s that can be added to and taken from in any
+/// order, since it keeps count of how many times it has carried past
+/// `u128::MAX`. Values too big for a `u128` are `None`, and leave the total
+/// unknown for as long as they're in it.
+#[derive(Debug, Default, Clone, Copy)]
+struct Total {
+    low: u128,
+    carries: u64,
+    oversized: u64,
+}
+
+impl PartData {
+    pub fn create(input: &str) -> Result<PartData, SchematicError> {
+        Self::create_with(input, Rules::default())
+    }
+
+    pub fn create_with(input: &str, rules: Rules) -> Result<PartData, SchematicError> {
+        let grid = Grid::create(input, rules.background);
+
+        let mut part_data = PartData {
+            numbers: vec![],
+            symbols: vec![],
+            rules,
+            grid,
+            number_map: HashMap::new(),
+            symbol_map: HashMap::new(),
+            part_number_sum: 0,
+            gear_ratio_sum: Total::default(),
+        };
+
+        for row in 0..part_data.grid.height() {
+            for col in 0..part_data.grid.width() {
+                part_data.add_symbol(Position { row, col });
+            }
+        }
+
+        for row in 0..part_data.grid.height() {
+            let numbers = find_numbers(part_data.grid.row(row))
+                .map_err(|col| SchematicError::NumberTooLarge(Position { row, col }))?;
+
+            for (col_span, value) in numbers {
+                part_data.add_number(row, col_span, value);
+            }
+        }
+
+        part_data.part_number_sum = part_data
+            .part_numbers()
+            .map(|number| u64::from(number.value))
+            .sum();
+
+        for symbol in 0..part_data.symbols.len() {
+            if let Some(ratio) = part_data.gear_ratio(symbol) {
+                part_data.gear_ratio_sum.add(ratio);
+            }
+        }
+
+        Ok(part_data)
+    }
+
+    /// Every number in the schematic, whether or not it's a part number. They
+    /// start out in reading order, but [`PartData::set_cell`] can shuffle them.
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    /// Every symbol in the schematic. Like [`PartData::numbers`], these start
+    /// out in reading order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Numbers that touch at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers.iter().filter(|number| number.is_part_number())
+    }
+
+    /// Numbers that don't touch any symbol.
+    pub fn isolated_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .filter(|number| !number.is_part_number())
+    }
+
+    /// Numbers that touch at least one `ch` symbol.
+    pub fn numbers_touching(&self, ch: char) -> impl Iterator<Item = &PartNumber> {
+        self.numbers.iter().filter(move |number| {
+            number
+                .adjacent_symbols
+                .iter()
+                .any(|&symbol| self.symbols[symbol].ch == ch)
+        })
+    }
+
+    /// Symbols that touch exactly `count` numbers.
+    pub fn symbols_with_part_count(&self, count: usize) -> impl Iterator<Item = &Symbol> {
+        self.symbols
+            .iter()
+            .filter(move |symbol| symbol.adjacent_parts.len() == count)
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Gear symbols that touch exactly as many numbers as the rules ask for.
+    pub fn gears(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols_with_part_count(self.rules.gear_part_count)
+            .filter(|symbol| self.rules.is_gear_symbol(symbol.ch))
+    }
+
+    pub fn get_part_number_sum(&self) -> u64 {
+        self.part_number_sum
+    }
+
+    /// The sum of every gear's ratio, or `None` if it doesn't fit in a `u128`.
+    pub fn get_gear_ratio_sum(&self) -> Option<u128> {
+        self.gear_ratio_sum.get()
+    }
+
+    /// Writes every number and symbol, along with their adjacency, as JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"numbers\": [");
+
+        for (i, number) in self.numbers.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(
+                json,
+                "{separator}\n    {{ \"value\": {}, \"row\": {}, \"col_span\": [{}, {}], \"adjacent_symbols\": {:?} }}",
+                number.value, number.row, number.col_span.start, number.col_span.end, number.adjacent_symbols,
+            ).unwrap();
+        }
+
+        json.push_str("\n  ],\n  \"symbols\": [");
+
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(
+                json,
+                "{separator}\n    {{ \"ch\": \"{}\", \"pos\": {{ \"row\": {}, \"col\": {} }}, \"adjacent_parts\": {:?} }}",
+                json_escape(symbol.ch), symbol.pos.row, symbol.pos.col, symbol.adjacent_parts,
+            ).unwrap();
+        }
+
+        json.push_str("\n  ]\n}\n");
+        json
+    }
+}
+
+impl PartData {
+    /// Records the symbol at `pos`, if there is one. Numbers touching it are
+    /// linked up as they're added.
+    fn add_symbol(&mut self, pos: Position) {
+        let ch = self.grid.get(pos.row, pos.col).unwrap();
+        if !self.rules.is_symbol(ch) {
+            return;
+        }
+
+        self.symbol_map.insert(pos, self.symbols.len());
+        self.symbols.push(Symbol {
+            ch,
+            pos,
+            adjacent_parts: vec![],
+        });
+    }
+
+    /// Records a number and links it to every symbol it touches. Returns its
+    /// index.
+    fn add_number(&mut self, row: usize, col_span: Range<usize>, value: u32) -> usize {
+        let index = self.numbers.len();
+
+        let adjacent_symbols = self
+            .grid
+            .neighbours(row, col_span.clone(), self.rules.neighbourhood)
+            .filter_map(|(row, col)| self.symbol_map.get(&Position { row, col }).copied())
+            .collect::<Vec<_>>();
+
+        for &symbol in adjacent_symbols.iter() {
+            self.symbols[symbol].adjacent_parts.push(index);
+        }
+
+        for col in col_span.clone() {
+            self.number_map.insert(Position { row, col }, index);
+        }
+
+        self.numbers.push(PartNumber {
+            value,
+            row,
+            col_span,
+            adjacent_symbols,
+        });
+
+        index
+    }
+
+    /// The ratio of the symbol at `index` if it's a gear. The ratio itself is
+    /// `None` if it doesn't fit in a `u128`.
+    fn gear_ratio(&self, index: usize) -> Option<Option<u128>> {
+        let symbol = &self.symbols[index];
+        let is_gear = self.rules.is_gear_symbol(symbol.ch)
+            && symbol.adjacent_parts.len() == self.rules.gear_part_count;
+
+        if is_gear {
+            let values = symbol
+                .adjacent_parts
+                .iter()
+                .map(|&part| self.numbers[part].value);
+            Some(self.rules.ratio(values))
+        } else {
+            None
+        }
+    }
+}
+
+impl Total {
+    fn add(&mut self, value: Option<u128>) {
+        let Some(value) = value else {
+            self.oversized += 1;
+            return;
+        };
+
+        let (low, carried) = self.low.overflowing_add(value);
+        self.low = low;
+        self.carries += u64::from(carried);
+    }
+
+    fn sub(&mut self, value: Option<u128>) {
+        let Some(value) = value else {
+            self.oversized -= 1;
+            return;
+        };
+
+        let (low, borrowed) = self.low.overflowing_sub(value);
+        self.low = low;
+        self.carries -= u64::from(borrowed);
+    }
+
+    fn get(&self) -> Option<u128> {
+        (self.carries == 0 && self.oversized == 0).then_some(self.low)
+    }
+}
+
+impl PartNumber {
+    pub fn is_part_number(&self) -> bool {
+        !self.adjacent_symbols.is_empty()
+    }
+}
+
+/// Every run of digits in `row`, along with the columns it covers. Fails with
+/// the first column of a number that doesn't fit in a `u32`.
+fn find_numbers(row: &[char]) -> Result<Vec<(Range<usize>, u32)>, usize> {
+    let mut numbers = vec![];
+    let mut start = None;
+
+    for (i, c) in row.iter().chain(Some(&'.')).enumerate() {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let number = row[s..i]
+                    .iter()
+                    .collect::<String>()
+                    .parse()
+                    .map_err(|_| s)?;
+                numbers.push((s..i, number));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(numbers)
+}
+
+fn json_escape(c: char) -> String {
+    match c {
+        '"' => "\\\"".to_string(),
+        '\\' => "\\\\".to_string(),
+        c if c.is_control() => format!("\\u{:04x}", c as u32),
+        c => c.to_string(),
+    }
+}
+
+impl fmt::Display for SchematicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchematicError::NumberTooLarge(Position { row, col }) => {
+                write!(f, "The number at ({row}, {col}) is too large")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchematicError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    fn part_values(part_data: &PartData) -> Vec<u32> {
+        part_data
+            .part_numbers()
+            .map(|number| number.value)
+            .collect()
+    }
+
+    fn gear_parts(part_data: &PartData) -> Vec<(Position, Vec<u32>)> {
+        part_data
+            .gears()
+            .map(|gear| {
+                let parts = gear
+                    .adjacent_parts
+                    .iter()
+                    .map(|&part| part_data.numbers()[part].value);
+                (gear.pos, parts.collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example() {
+        let part_data = PartData::create(EXAMPLE).unwrap();
+
+        assert_eq!(
+            part_values(&part_data),
+            [467, 35, 633, 617, 592, 755, 664, 598]
+        );
+        assert_eq!(
+            gear_parts(&part_data),
+            [
+                (Position { row: 1, col: 3 }, vec![467, 35]),
+                (Position { row: 8, col: 5 }, vec![755, 598]),
+            ]
+        );
+        assert_eq!(part_data.get_part_number_sum(), 4361);
+        assert_eq!(part_data.get_gear_ratio_sum(), Some(467835));
+    }
+
+    #[test]
+    fn uneven_line_lengths() {
+        let ragged = EXAMPLE
+            .lines()
+            .map(|line| line.trim_end_matches('.'))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let expected = PartData::create(EXAMPLE).unwrap();
+        let part_data = PartData::create(&ragged).unwrap();
+
+        assert_eq!(part_values(&part_data), part_values(&expected));
+        assert_eq!(gear_parts(&part_data), gear_parts(&expected));
+
+        // A number past the end of a shorter line above still sees the
+        // symbol below it.
+        let part_data = PartData::create("1\n..\n.12\n...#").unwrap();
+        assert_eq!(part_values(&part_data), [12]);
+    }
+
+    #[test]
+    fn sums_wider_than_numbers() {
+        let mut part_data =
+            PartData::create("4000000000#4000000000\n..........#..........").unwrap();
+        assert_eq!(part_data.get_part_number_sum(), 8000000000);
+
+        part_data.set_cell(1, 0, '$').unwrap();
+        part_data.set_cell(1, 9, '1').unwrap();
+        assert_eq!(part_data.get_part_number_sum(), 8000000001);
+    }
+
+    #[test]
+    fn gear_ratio_totals_carry() {
+        let mut total = Total::default();
+        total.add(Some(u128::MAX));
+        total.add(Some(2));
+        assert_eq!(total.get(), None);
+
+        total.sub(Some(u128::MAX));
+        assert_eq!(total.get(), Some(2));
+
+        total.add(None);
+        assert_eq!(total.get(), None);
+
+        total.sub(None);
+        assert_eq!(total.get(), Some(2));
+    }
+
+    #[test]
+    fn number_too_large() {
+        let err = PartData::create("..\n.99999999999*").unwrap_err();
+        assert_eq!(
+            err,
+            SchematicError::NumberTooLarge(Position { row: 1, col: 1 })
+        );
+
+        let mut part_data = PartData::create("4294967.95*").unwrap();
+        let err = part_data.set_cell(0, 7, '3').unwrap_err();
+        assert_eq!(
+            err,
+            SchematicError::NumberTooLarge(Position { row: 0, col: 0 })
+        );
+        assert_eq!(part_values(&part_data), [95]);
+
+        part_data.set_cell(0, 7, '1').unwrap();
+        assert_eq!(part_values(&part_data), [4294967195]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Groups part numbers and symbols into machines: sets of symbols and numbers
+//! that all touch each other, directly or through other members.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{PartData, Position};
+
+/// A connected group of symbols and the numbers around them. Symbols and
+/// numbers join whenever they touch, including numbers touching numbers, so a
+/// number can be in a machine without touching a symbol itself. A group of
+/// numbers with no symbol at all isn't a machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub top_left: Position,
+    /// The bottom right corner of the bounding box, inclusive.
+    pub bottom_right: Position,
+    /// Indices into [`PartData::numbers`].
+    pub parts: Vec<usize>,
+    /// Indices into [`PartData::symbols`].
+    pub symbols: Vec<usize>,
+    pub symbol_counts: BTreeMap<char, usize>,
+    pub part_sum: u64,
+}
+
+struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl PartData {
+    /// Every machine in the schematic, ordered by the top left corner of
+    /// their bounding boxes.
+    pub fn machines(&self) -> Vec<Machine> {
+        // Symbols are nodes `0..symbols.len()`, numbers come after them.
+        let number_node = |number: usize| self.symbols.len() + number;
+        let mut set = DisjointSet::create(self.symbols.len() + self.numbers.len());
+
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            for &part in symbol.adjacent_parts.iter() {
+                set.union(i, number_node(part));
+            }
+
+            let Position { row, col } = symbol.pos;
+            for (row, col) in self
+                .grid
+                .neighbours(row, col..col + 1, self.rules.neighbourhood)
+            {
+                if let Some(&other) = self.symbol_map.get(&Position { row, col }) {
+                    set.union(i, other);
+                }
+            }
+        }
+
+        for (i, number) in self.numbers.iter().enumerate() {
+            for (row, col) in self.grid.neighbours(
+                number.row,
+                number.col_span.clone(),
+                self.rules.neighbourhood,
+            ) {
+                if let Some(&other) = self.number_map.get(&Position { row, col }) {
+                    set.union(number_node(i), number_node(other));
+                }
+            }
+        }
+
+        let mut machines: HashMap<usize, Machine> = HashMap::new();
+
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            let machine = machines
+                .entry(set.find(i))
+                .or_insert_with(|| Machine::create(symbol.pos));
+            machine.include(symbol.pos);
+            machine.symbols.push(i);
+            *machine.symbol_counts.entry(symbol.ch).or_insert(0) += 1;
+        }
+
+        for (i, number) in self.numbers.iter().enumerate() {
+            // Numbers that aren't connected to a symbol don't belong to any
+            // machine.
+            let Some(machine) = machines.get_mut(&set.find(number_node(i))) else {
+                continue;
+            };
+
+            machine.include(Position {
+                row: number.row,
+                col: number.col_span.start,
+            });
+            machine.include(Position {
+                row: number.row,
+                col: number.col_span.end - 1,
+            });
+            machine.parts.push(i);
+            machine.part_sum += u64::from(number.value);
+        }
+
+        let mut machines = machines.into_values().collect::<Vec<_>>();
+        machines.sort_by_key(|machine| (machine.top_left, machine.bottom_right));
+
+        machines
+    }
+}
+
+impl Machine {
+    fn create(pos: Position) -> Self {
+        Self {
+            top_left: pos,
+            bottom_right: pos,
+            parts: vec![],
+            symbols: vec![],
+            symbol_counts: BTreeMap::new(),
+            part_sum: 0,
+        }
+    }
+
+    fn include(&mut self, pos: Position) {
+        self.top_left.row = self.top_left.row.min(pos.row);
+        self.top_left.col = self.top_left.col.min(pos.col);
+        self.bottom_right.row = self.bottom_right.row.max(pos.row);
+        self.bottom_right.col = self.bottom_right.col.max(pos.col);
+    }
+}
+
+impl DisjointSet {
+    fn create(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Point everything on the way straight at the root.
+        let mut node = node;
+        while self.parents[node] != root {
+            node = std::mem::replace(&mut self.parents[node], root);
+        }
+
+        root
+    }
+
+    /// Joins the sets holding `a` and `b`, hanging the smaller one off the
+    /// bigger one so that chains stay short.
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        let (small, big) = if self.sizes[a] < self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = big;
+        self.sizes[big] += self.sizes[small];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PartData;
+
+    #[test]
+    fn numbers_join_through_each_other() {
+        let part_data = PartData::create("#12..\n..34$").unwrap();
+        let machines = part_data.machines();
+
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].parts, [0, 1]);
+        assert_eq!(machines[0].symbols, [0, 1]);
+        assert_eq!(machines[0].part_sum, 46);
+    }
+
+    #[test]
+    fn long_rows_of_symbols() {
+        let row = "#".repeat(200_000);
+        let part_data = PartData::create(&format!("{row}\n1")).unwrap();
+        let machines = part_data.machines();
+
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].symbols.len(), 200_000);
+        assert_eq!(machines[0].parts, [0]);
+    }
+
+    #[test]
+    fn numbers_without_symbols_are_not_machines() {
+        let part_data = PartData::create("12...\n.34.5\n....#").unwrap();
+        let machines = part_data.machines();
+
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].parts, [2]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Draws a schematic with every number and symbol coloured by how it was
+//! counted, so that it's easy to see why a number was or wasn't a part number.
+
+use std::{collections::HashSet, fmt::Write};
+
+use crate::{PartData, Position};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Style {
+    Background,
+    PartNumber,
+    NonPartNumber,
+    GearNumber,
+    Symbol,
+    Gear,
+}
+
+struct Segment {
+    text: String,
+    style: Style,
+    /// Why the segment has the style it does.
+    note: Option<String>,
+}
+
+const LEGEND: [(Style, &str); 5] = [
+    (Style::PartNumber, "part number"),
+    (Style::NonPartNumber, "not a part number"),
+    (Style::GearNumber, "gear ratio number"),
+    (Style::Symbol, "symbol"),
+    (Style::Gear, "gear"),
+];
+
+/// Renders the schematic with ANSI colour codes, followed by a legend.
+pub fn ansi(data: &PartData) -> String {
+    let mut out = String::new();
+
+    for row in segments(data) {
+        for segment in row {
+            match ansi_code(segment.style) {
+                Some(code) => write!(out, "\x1b[{code}m{}\x1b[0m", segment.text).unwrap(),
+                None => out.push_str(&segment.text),
+            }
+        }
+        out.push('\n');
+    }
+
+    out.push('\n');
+    for (style, name) in LEGEND {
+        write!(out, "\x1b[{}m{name}\x1b[0m  ", ansi_code(style).unwrap()).unwrap();
+    }
+    out.push('\n');
+
+    out
+}
+
+/// Renders the schematic as a standalone HTML page. Hovering over a number or
+/// symbol shows what it touches.
+pub fn html(data: &PartData) -> String {
+    let mut out = String::from(concat!(
+        "<!DOCTYPE html>\n",
+        "<html>\n",
+        "<head>\n",
+        "<meta charset=\"utf-8\">\n",
+        "<title>Engine schematic</title>\n",
+        "<style>\n",
+        "body { background: #1e1e1e; color: #666; font-family: monospace; }\n",
+        "pre { line-height: 1.2; }\n",
+        ".part { color: #6c6; }\n",
+        ".non-part { color: #e55; }\n",
+        ".gear-number { color: #fc3; font-weight: bold; }\n",
+        ".symbol { color: #5cf; }\n",
+        ".gear { color: #1e1e1e; background: #fc3; font-weight: bold; }\n",
+        "</style>\n",
+        "</head>\n",
+        "<body>\n",
+    ));
+
+    out.push_str("<p>");
+    for (style, name) in LEGEND {
+        write!(
+            out,
+            "<span class=\"{}\">{name}</span> ",
+            html_class(style).unwrap()
+        )
+        .unwrap();
+    }
+    out.push_str("</p>\n<pre>\n");
+
+    for row in segments(data) {
+        for segment in row {
+            let text = html_escape(&segment.text);
+
+            match (html_class(segment.style), segment.note) {
+                (Some(class), Some(note)) => write!(
+                    out,
+                    "<span class=\"{class}\" title=\"{}\">{text}</span>",
+                    html_escape(&note)
+                )
+                .unwrap(),
+                (Some(class), None) => {
+                    write!(out, "<span class=\"{class}\">{text}</span>").unwrap()
+                }
+                (None, _) => out.push_str(&text),
+            }
+        }
+        out.push('\n');
+    }
+
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+/// Splits every row of the schematic into styled runs: one per number, one
+/// per symbol and one per background cell.
+fn segments(data: &PartData) -> Vec<Vec<Segment>> {
+    let gears = data.gears().map(|gear| gear.pos).collect::<HashSet<_>>();
+    let gear_numbers = data
+        .gears()
+        .flat_map(|gear| gear.adjacent_parts.iter().copied())
+        .collect::<HashSet<_>>();
+
+    let describe = |symbols: &[usize]| {
+        symbols
+            .iter()
+            .map(|&symbol| {
+                let symbol = &data.symbols[symbol];
+                format!("{} at {},{}", symbol.ch, symbol.pos.row, symbol.pos.col)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut rows = vec![];
+
+    for row in 0..data.grid.height() {
+        let cells = data.grid.row(row);
+        let mut segments = vec![];
+        let mut col = 0;
+
+        while col < cells.len() {
+            let pos = Position { row, col };
+
+            // Numbers are only ever reached at their first cell, since the
+            // scan skips past the rest of them.
+            let segment = if let Some(&i) = data.number_map.get(&pos) {
+                let number = &data.numbers[i];
+                let (style, note) = if gear_numbers.contains(&i) {
+                    (
+                        Style::GearNumber,
+                        format!(
+                            "{} is part of a gear ratio; touches {}",
+                            number.value,
+                            describe(&number.adjacent_symbols)
+                        ),
+                    )
+                } else if number.is_part_number() {
+                    (
+                        Style::PartNumber,
+                        format!(
+                            "{} is a part number; touches {}",
+                            number.value,
+                            describe(&number.adjacent_symbols)
+                        ),
+                    )
+                } else {
+                    (
+                        Style::NonPartNumber,
+                        format!(
+                            "{} isn't a part number; it touches no symbols",
+                            number.value
+                        ),
+                    )
+                };
+
+                col = number.col_span.end;
+                Segment {
+                    text: cells[number.col_span.clone()].iter().collect(),
+                    style,
+                    note: Some(note),
+                }
+            } else if let Some(&i) = data.symbol_map.get(&pos) {
+                let symbol = &data.symbols[i];
+                let values = symbol
+                    .adjacent_parts
+                    .iter()
+                    .map(|&part| data.numbers[part].value.to_string())
+                    .collect::<Vec<_>>();
+
+                let (style, note) = if gears.contains(&pos) {
+                    let ratio = data
+                        .rules
+                        .ratio(
+                            symbol
+                                .adjacent_parts
+                                .iter()
+                                .map(|&part| data.numbers[part].value),
+                        )
+                        .map_or("too large to show".to_string(), |ratio| ratio.to_string());
+                    (
+                        Style::Gear,
+                        format!("gear with ratio {ratio} from {}", values.join(", ")),
+                    )
+                } else if values.is_empty() {
+                    (Style::Symbol, "symbol touching no numbers".to_string())
+                } else {
+                    (
+                        Style::Symbol,
+                        format!("symbol touching {}", values.join(", ")),
+                    )
+                };
+
+                col += 1;
+                Segment {
+                    text: symbol.ch.to_string(),
+                    style,
+                    note: Some(note),
+                }
+            } else {
+                col += 1;
+                Segment {
+                    text: cells[col - 1].to_string(),
+                    style: Style::Background,
+                    note: None,
+                }
+            };
+
+            segments.push(segment);
+        }
+
+        rows.push(segments);
+    }
+
+    rows
+}
+
+fn ansi_code(style: Style) -> Option<&'static str> {
+    match style {
+        Style::Background => None,
+        Style::PartNumber => Some("32"),
+        Style::NonPartNumber => Some("31"),
+        Style::GearNumber => Some("1;33"),
+        Style::Symbol => Some("36"),
+        Style::Gear => Some("1;30;43"),
+    }
+}
+
+fn html_class(style: Style) -> Option<&'static str> {
+    match style {
+        Style::Background => None,
+        Style::PartNumber => Some("part"),
+        Style::NonPartNumber => Some("non-part"),
+        Style::GearNumber => Some("gear-number"),
+        Style::Symbol => Some("symbol"),
+        Style::Gear => Some("gear"),
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMATIC: &str = "12*3.\n....7\n";
+
+    #[test]
+    fn ansi_styles() {
+        let data = PartData::create(SCHEMATIC).unwrap();
+        let lines = ansi(&data).lines().map(str::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[..2],
+            [
+                "\x1b[1;33m12\x1b[0m\x1b[1;30;43m*\x1b[0m\x1b[1;33m3\x1b[0m.",
+                "....\x1b[31m7\x1b[0m",
+            ]
+        );
+    }
+
+    #[test]
+    fn html_styles() {
+        let data = PartData::create(SCHEMATIC).unwrap();
+        let html = html(&data);
+
+        assert!(html.contains(
+            "<span class=\"gear-number\" title=\"12 is part of a gear ratio; touches * at 0,2\">12</span>\
+             <span class=\"gear\" title=\"gear with ratio 36 from 12, 3\">*</span>\
+             <span class=\"gear-number\" title=\"3 is part of a gear ratio; touches * at 0,2\">3</span>.\n"
+        ));
+        assert!(html.contains(
+            "....<span class=\"non-part\" title=\"7 isn't a part number; it touches no symbols\">7</span>\n"
+        ));
+    }
+}
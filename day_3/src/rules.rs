@@ -0,0 +1,170 @@
+/// Decides what counts as a symbol and a gear when reading a schematic.
+///
+/// The default is the puzzle's own rules: `.` is empty space, every other
+/// non-digit is a symbol, and a gear is a `*` touching exactly two numbers
+/// whose ratio is their product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// The character used for empty space. Short lines are padded with it.
+    pub background: char,
+    pub symbols: SymbolRule,
+    /// Characters that can be gears. These always count as symbols, whatever
+    /// `symbols` says.
+    pub gear_symbols: Vec<char>,
+    /// How many numbers a gear symbol has to touch to be a gear.
+    pub gear_part_count: usize,
+    pub ratio: RatioOp,
+    pub neighbourhood: Neighbourhood,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolRule {
+    /// Anything that isn't a digit or the background.
+    AnyOther,
+    /// Only these characters. Anything else is treated like the background.
+    Only(Vec<char>),
+}
+
+/// How the numbers touching a gear combine into its ratio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RatioOp {
+    Product,
+    Sum,
+}
+
+/// Which cells count as touching.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Only cells sharing an edge.
+    Four,
+    /// Cells sharing an edge or a corner.
+    Eight,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            background: '.',
+            symbols: SymbolRule::AnyOther,
+            gear_symbols: vec!['*'],
+            gear_part_count: 2,
+            ratio: RatioOp::Product,
+            neighbourhood: Neighbourhood::Eight,
+        }
+    }
+}
+
+impl Rules {
+    pub fn is_symbol(&self, c: char) -> bool {
+        if self.is_gear_symbol(c) {
+            return true;
+        }
+
+        match self.symbols {
+            SymbolRule::AnyOther => !c.is_numeric() && c != self.background,
+            SymbolRule::Only(ref symbols) => symbols.contains(&c),
+        }
+    }
+
+    pub fn is_gear_symbol(&self, c: char) -> bool {
+        self.gear_symbols.contains(&c)
+    }
+
+    /// Combines the numbers touching a gear, or gives `None` if the ratio
+    /// doesn't fit in a `u128`.
+    pub fn ratio(&self, mut values: impl Iterator<Item = u32>) -> Option<u128> {
+        match self.ratio {
+            RatioOp::Product => {
+                values.try_fold(1u128, |ratio, value| ratio.checked_mul(value.into()))
+            }
+            RatioOp::Sum => values.try_fold(0u128, |ratio, value| ratio.checked_add(value.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PartData;
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    fn part_values(part_data: &PartData) -> Vec<u32> {
+        part_data
+            .part_numbers()
+            .map(|number| number.value)
+            .collect()
+    }
+
+    #[test]
+    fn four_connected() {
+        let rules = Rules {
+            neighbourhood: Neighbourhood::Four,
+            ..Rules::default()
+        };
+
+        let part_data = PartData::create_with("1.2\n.#.\n.3.", rules).unwrap();
+        assert_eq!(part_values(&part_data), [3]);
+
+        let part_data = PartData::create("1.2\n.#.\n.3.").unwrap();
+        assert_eq!(part_values(&part_data), [1, 2, 3]);
+    }
+
+    #[test]
+    fn sum_ratio() {
+        let rules = Rules {
+            ratio: RatioOp::Sum,
+            ..Rules::default()
+        };
+
+        let part_data = PartData::create_with(EXAMPLE, rules).unwrap();
+        assert_eq!(part_data.get_gear_ratio_sum(), Some(467 + 35 + 755 + 598));
+    }
+
+    #[test]
+    fn only_some_symbols() {
+        let rules = Rules {
+            symbols: SymbolRule::Only(vec!['#']),
+            ..Rules::default()
+        };
+
+        // `*` still counts, since it's a gear symbol.
+        let part_data = PartData::create_with(EXAMPLE, rules).unwrap();
+        assert_eq!(part_values(&part_data), [467, 35, 633, 617, 755, 598]);
+    }
+
+    #[test]
+    fn gears_with_one_part() {
+        let rules = Rules {
+            gear_part_count: 1,
+            ..Rules::default()
+        };
+
+        let part_data = PartData::create_with(EXAMPLE, rules).unwrap();
+        assert_eq!(part_data.get_gear_ratio_sum(), Some(617));
+    }
+
+    #[test]
+    fn ratios_too_large() {
+        let part_data = PartData::create("100000*100000").unwrap();
+        assert_eq!(part_data.get_gear_ratio_sum(), Some(10_000_000_000));
+
+        let rules = Rules::default();
+        assert_eq!(
+            rules.ratio([u32::MAX; 4].into_iter()),
+            Some(u128::from(u32::MAX).pow(4))
+        );
+        assert_eq!(rules.ratio([u32::MAX; 5].into_iter()), None);
+    }
+}
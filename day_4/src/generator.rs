@@ -0,0 +1,514 @@
+//! Builds scratchcard decks with a known answer: a given number of cards that
+//! score a target number of points and cascade into a target number of
+//! scratchcards.
+//!
+//! The search picks a win count for every card from the last one back. One
+//! copy of a card makes itself plus whatever the copies it wins make, so
+//! once the cards after it are picked, what a card adds to the total is
+//! settled. Branches are cut off as soon as the cards left can't make up the
+//! points or the scratchcards still needed, and dead ends are remembered so
+//! that they're only explored once. The search is exhaustive, so when it
+//! fails there really is no such deck.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+};
+
+use crate::scoring::Scoring;
+
+/// The deck to build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckSpec {
+    pub deck_size: usize,
+    /// The part 1 answer.
+    pub target_points: u64,
+    /// The part 2 answer.
+    pub target_count: u64,
+    /// How many winning numbers each card has.
+    pub winning_len: usize,
+    /// How many numbers the player has on each card.
+    pub player_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// Cards need 99 distinct numbers at most, since every number is one or
+    /// two digits.
+    TooManyNumbers,
+    /// No deck of this size hits both targets.
+    Unreachable,
+}
+
+impl DeckSpec {
+    /// A deck laid out like the puzzle input, with 10 winning numbers and 25
+    /// player numbers per card.
+    pub fn create(deck_size: usize, target_points: u64, target_count: u64) -> Self {
+        Self {
+            deck_size,
+            target_points,
+            target_count,
+            winning_len: 10,
+            player_len: 25,
+        }
+    }
+
+    fn max_wins(&self) -> usize {
+        self.winning_len.min(self.player_len)
+    }
+}
+
+/// Builds the deck described by `spec`, with points counted by `scoring`.
+/// The result is one `Card N: ... | ...` line per card.
+pub fn generate(spec: &DeckSpec, scoring: &dyn Scoring) -> Result<String, GenerateError> {
+    if spec.winning_len + spec.player_len > 99 {
+        return Err(GenerateError::TooManyNumbers);
+    }
+
+    let win_counts = find_win_counts(spec, scoring)?;
+    let id_width = spec.deck_size.to_string().len();
+
+    let mut deck = String::new();
+    for (i, &win_count) in win_counts.iter().enumerate() {
+        // Rotate through 1..=99 so that cards don't all look the same. The
+        // first `win_count` winning numbers are the ones the player has.
+        let pool = (0..99).map(|n| (n + 7 * i) % 99 + 1).collect::<Vec<_>>();
+
+        let winning_nums = &pool[..spec.winning_len];
+        let player_nums = pool[..win_count].iter().chain(
+            pool[spec.winning_len..]
+                .iter()
+                .take(spec.player_len - win_count),
+        );
+
+        write!(deck, "Card {:>id_width$}:", i + 1).unwrap();
+        for num in winning_nums {
+            write!(deck, " {num:>2}").unwrap();
+        }
+        deck.push_str(" |");
+        for num in player_nums {
+            write!(deck, " {num:>2}").unwrap();
+        }
+        deck.push('\n');
+    }
+
+    Ok(deck)
+}
+
+/// The number of winning numbers each card in the deck should match.
+pub fn find_win_counts(
+    spec: &DeckSpec,
+    scoring: &dyn Scoring,
+) -> Result<Vec<usize>, GenerateError> {
+    let n = spec.deck_size;
+    let width = spec.max_wins();
+    let caps = (0..n).map(|i| width.min(n - 1 - i)).collect::<Vec<_>>();
+
+    // `min_points[i]` and `max_points[i]` are the fewest and most points that
+    // cards `..i` can score between them.
+    let mut min_points = vec![0u128; n + 1];
+    let mut max_points = vec![0u128; n + 1];
+    for i in 0..n {
+        let scores = (0..=caps[i]).map(|w| u128::from(scoring.score(w)));
+        min_points[i + 1] = min_points[i] + scores.clone().min().unwrap();
+        max_points[i + 1] = max_points[i] + scores.max().unwrap();
+    }
+
+    // `limits[k]` is for when no card can win more than `k`. If cards `..i`
+    // all win as much as they can, `most_before[i]` is how many copies of
+    // themselves they make and `reach[i][t]` is how many copies of card
+    // `i + t` they win.
+    let limits = (0..=width)
+        .map(|limit| {
+            let mut most_before = vec![0u128; n + 1];
+            let mut reach = vec![vec![0u128; width]; n + 1];
+            let mut won = vec![0u128; n];
+            for i in 0..n {
+                for (t, reached) in reach[i].iter_mut().enumerate() {
+                    *reached = won.get(i + t).copied().unwrap_or(0);
+                }
+
+                let copies = won[i].saturating_add(1);
+                most_before[i + 1] = most_before[i].saturating_add(copies);
+                for won in &mut won[i + 1..=i + caps[i].min(limit)] {
+                    *won = won.saturating_add(copies);
+                }
+            }
+            Limit { most_before, reach }
+        })
+        .collect::<Vec<_>>();
+
+    // The extra points, over the least any card can score, it takes to win
+    // `w`.
+    let least = (0..=width).map(|w| scoring.score(w)).min().unwrap();
+    let extra_points = (0..=width)
+        .map(|w| u128::from(scoring.score(w) - least))
+        .collect::<Vec<_>>();
+
+    // Every match a card wins adds at least one scratchcard, so the best
+    // points per match limits how many points the scratchcards left to win
+    // can buy. That only holds if no card scores more by matching nothing.
+    let zero = scoring.score(0);
+    let points_per_win = (1..=width)
+        .map(|w| scoring.score(w).checked_sub(zero).map(|points| (points, w)))
+        .try_fold((0, 1), |best: (u64, usize), option| {
+            let (points, w) = option?;
+            let better = u128::from(points) * best.1 as u128 > u128::from(best.0) * w as u128;
+            Some(if better { (points, w) } else { best })
+        });
+
+    let points = u128::from(spec.target_points);
+    let count = u128::from(spec.target_count);
+    let mut search = Search {
+        scoring,
+        caps,
+        min_points,
+        max_points,
+        limits,
+        cheapest_win: extra_points[1..].iter().copied().min().unwrap_or(0),
+        extra_points,
+        points_per_win,
+        target_points: points,
+        target_count: count,
+        win_counts: vec![0; n],
+        made: vec![0; n],
+        dead_ends: HashSet::new(),
+        budget: 0,
+        order: Order::Balanced,
+        rng: Rng(0x2545_F491_4F6C_DD1D),
+    };
+
+    if !search.in_reach(n, points, count) {
+        return Err(GenerateError::Unreachable);
+    }
+
+    // Which order pays off depends a lot on the targets, so each attempt
+    // tries the next one with a bit more to spend. Dead ends carry over, so
+    // every attempt picks up where the last left off and the search still
+    // ends when there's nothing left to explore.
+    let mut orders = [
+        Order::Balanced,
+        Order::MostWins,
+        Order::Level,
+        Order::FewestWins,
+    ]
+    .into_iter()
+    .cycle();
+    let mut budget = 1000;
+    loop {
+        search.budget = budget;
+        search.order = orders.next().unwrap();
+        match search.search(n, points, count) {
+            Outcome::Found => return Ok(search.win_counts),
+            Outcome::DeadEnd => return Err(GenerateError::Unreachable),
+            Outcome::OutOfTime => budget += budget / 10,
+        }
+    }
+}
+
+/// The state of the search for win counts. Cards `i..` have been picked, and
+/// `made[j]` for `j >= i` is how many scratchcards one copy of card `j` makes.
+struct Search<'a> {
+    scoring: &'a dyn Scoring,
+    caps: Vec<usize>,
+    min_points: Vec<u128>,
+    max_points: Vec<u128>,
+    limits: Vec<Limit>,
+    extra_points: Vec<u128>,
+    cheapest_win: u128,
+    /// The most points, over what matching nothing scores, that a card can
+    /// score per match, as a fraction.
+    points_per_win: Option<(u64, usize)>,
+    target_points: u128,
+    target_count: u128,
+    win_counts: Vec<usize>,
+    made: Vec<u128>,
+    /// States already known not to lead anywhere, as the number of cards left
+    /// to pick, the points and scratchcards left, and what the cards they can
+    /// reach make.
+    dead_ends: HashSet<(usize, u128, u128, Vec<u128>)>,
+    /// How many more states this attempt may visit.
+    budget: u64,
+    order: Order,
+    rng: Rng,
+}
+
+struct Limit {
+    most_before: Vec<u128>,
+    reach: Vec<Vec<u128>>,
+}
+
+/// The order in which to try win counts for a card. The noise on top breaks
+/// ties differently from one attempt to the next.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Order {
+    /// Keep points and scratchcards level, leaning towards more wins.
+    Balanced,
+    MostWins,
+    /// Keep points and scratchcards level.
+    Level,
+    FewestWins,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Outcome {
+    Found,
+    DeadEnd,
+    OutOfTime,
+}
+
+impl Search<'_> {
+    /// Picks win counts for cards `..i` scoring exactly `points_left` and
+    /// making exactly `count_left` scratchcards.
+    fn search(&mut self, i: usize, points_left: u128, count_left: u128) -> Outcome {
+        if i == 0 {
+            return Outcome::Found;
+        }
+
+        let horizon = (i + self.limits[0].reach[i].len()).min(self.made.len());
+        let key = (i, points_left, count_left, self.made[i..horizon].to_vec());
+        if self.dead_ends.contains(&key) {
+            return Outcome::DeadEnd;
+        }
+
+        if self.budget == 0 {
+            return Outcome::OutOfTime;
+        }
+        self.budget -= 1;
+
+        let card = i - 1;
+        let mut options = vec![];
+        for w in 0..=self.caps[card] {
+            let Some(points) = points_left.checked_sub(self.scoring.score(w).into()) else {
+                continue;
+            };
+
+            let made = self.made[card + 1..=card + w]
+                .iter()
+                .fold(1u128, |total, &made| total.saturating_add(made));
+            let Some(count) = count_left.checked_sub(made) else {
+                continue;
+            };
+
+            self.made[card] = made;
+            if self.in_reach(card, points, count) {
+                // How far the points spent so far run ahead of the
+                // scratchcards made, which the balanced orders try to keep
+                // level.
+                let spent = 1.0 - points as f64 / self.target_points.max(1) as f64;
+                let made_so_far = (self.target_count - count + card as u128) as f64
+                    / self.target_count.max(1) as f64;
+                let drift = (spent - made_so_far).abs() * 10.0;
+                let (rank, noise) = match self.order {
+                    Order::Balanced => (drift - w as f64 * 0.1, 0.5),
+                    Order::MostWins => (-(w as f64), 0.5),
+                    Order::Level => (drift, 1.0),
+                    Order::FewestWins => (w as f64, 0.5),
+                };
+                let noise = self.rng.below(1000) as f64 / 1000.0 * noise;
+                options.push((rank + noise, w, made, points, count));
+            }
+        }
+        options.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut outcome = Outcome::DeadEnd;
+        for (_, w, made, points, count) in options {
+            self.win_counts[card] = w;
+            self.made[card] = made;
+            match self.search(card, points, count) {
+                Outcome::Found => return Outcome::Found,
+                Outcome::DeadEnd => {}
+                Outcome::OutOfTime => outcome = Outcome::OutOfTime,
+            }
+        }
+
+        if outcome == Outcome::DeadEnd {
+            self.dead_ends.insert(key);
+        }
+        outcome
+    }
+
+    /// An upper bound on the scratchcards cards `..i` make if only `winners`
+    /// of them win anything, and none wins more than `limit`.
+    fn most_with_winners(&self, i: usize, winners: u128, limit: usize) -> u128 {
+        let horizon = (i + limit).min(self.made.len());
+        let reachable = self.made[i..horizon].iter().copied().max().unwrap_or(0);
+
+        // The `k`th winner from the end makes at most `made`, and the rest
+        // make one scratchcard each.
+        let mut total = i as u128 - winners;
+        let mut made = 1u128;
+        for _ in 0..winners {
+            made = made
+                .max(reachable)
+                .saturating_mul(limit as u128)
+                .saturating_add(1);
+            total = total.saturating_add(made);
+        }
+
+        total
+    }
+
+    /// Whether cards `..i` could score `points_left` and make `count_left`
+    /// scratchcards, given what the cards after them make.
+    fn in_reach(&self, i: usize, points_left: u128, count_left: u128) -> bool {
+        let (points_low, points_high) = (self.min_points[i], self.max_points[i]);
+        if points_left < points_low || points_left > points_high {
+            return false;
+        }
+
+        // Every card makes at least one scratchcard: itself.
+        let fewest = i as u128;
+        if count_left < fewest {
+            return false;
+        }
+
+        if let Some((points, wins)) = self.points_per_win {
+            let extra_points = (points_left - points_low).saturating_mul(wins as u128);
+            if extra_points > (count_left - fewest).saturating_mul(points.into()) {
+                return false;
+            }
+        }
+
+        let spare = points_left - points_low;
+        let limit = self
+            .extra_points
+            .iter()
+            .rposition(|&extra| extra <= spare)
+            .unwrap();
+        if let Some(winners) = spare.checked_div(self.cheapest_win) {
+            if winners < i as u128 && count_left > self.most_with_winners(i, winners, limit) {
+                return false;
+            }
+        }
+
+        let limit = &self.limits[limit];
+        let most = limit.reach[i]
+            .iter()
+            .zip(&self.made[i..])
+            .fold(limit.most_before[i], |total, (&copies, &made)| {
+                total.saturating_add(copies.saturating_mul(made))
+            });
+        count_left <= most
+    }
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % bound as u64) as usize
+    }
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::TooManyNumbers => {
+                write!(f, "Cards can't have more than 99 numbers between them")
+            }
+            GenerateError::Unreachable => write!(f, "No deck of that size hits both targets"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scoring::{Doubling, Fibonacci, Linear, Table},
+        OutOfRangePolicy,
+    };
+
+    #[test]
+    fn generated_deck_has_the_requested_answers() {
+        let deck = generate(&DeckSpec::create(6, 13, 30), &Doubling).unwrap();
+        let cards = crate::parse_deck(&deck).unwrap();
+
+        assert_eq!(crate::get_total_points(&cards, &Doubling), Some(13));
+        assert_eq!(
+            crate::get_total_scratchcard_count(&cards, OutOfRangePolicy::Error),
+            Ok(30)
+        );
+    }
+
+    fn assert_generates(spec: &DeckSpec, scoring: &dyn Scoring) {
+        let deck = generate(spec, scoring).unwrap();
+        let cards = crate::parse_deck(&deck).unwrap();
+
+        assert_eq!(cards.len(), spec.deck_size);
+        assert_eq!(
+            crate::get_total_points(&cards, scoring),
+            Some(spec.target_points)
+        );
+        assert_eq!(
+            crate::get_total_scratchcard_count(&cards, OutOfRangePolicy::Error),
+            Ok(spec.target_count)
+        );
+    }
+
+    #[test]
+    fn finds_decks_a_greedy_start_misses() {
+        let table = Table(vec![0, 5, 7, 20]);
+        assert_generates(&DeckSpec::create(3, 10, 6), &table);
+        assert_generates(&DeckSpec::create(9, 147, 484), &Doubling);
+    }
+
+    #[test]
+    fn reproduces_random_decks() {
+        let schemes: [&dyn Scoring; 4] =
+            [&Doubling, &Linear, &Fibonacci, &Table(vec![0, 5, 7, 20])];
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..200 {
+            let deck_size = rng.below(13);
+            let scoring = schemes[rng.below(schemes.len())];
+            let mut spec = DeckSpec::create(deck_size, 0, 0);
+            spec.winning_len = 1 + rng.below(5);
+            spec.player_len = spec.winning_len + rng.below(3);
+
+            // Any deck with these win counts has answers the generator must
+            // be able to hit.
+            let win_counts = (0..deck_size)
+                .map(|i| rng.below(spec.max_wins().min(deck_size - 1 - i) + 1))
+                .collect::<Vec<_>>();
+            let mut copies = vec![1; deck_size];
+            for (i, &win_count) in win_counts.iter().enumerate() {
+                spec.target_points += scoring.score(win_count);
+                spec.target_count += copies[i];
+                let (made, after) = copies.split_at_mut(i + 1);
+                for copies in &mut after[..win_count] {
+                    *copies += made[i];
+                }
+            }
+
+            assert_generates(&spec, scoring);
+        }
+    }
+
+    #[test]
+    fn tiny_decks() {
+        assert_eq!(
+            find_win_counts(&DeckSpec::create(0, 0, 0), &Doubling),
+            Ok(vec![])
+        );
+        assert_eq!(
+            find_win_counts(&DeckSpec::create(0, 0, 5), &Doubling),
+            Err(GenerateError::Unreachable)
+        );
+        assert_eq!(
+            find_win_counts(&DeckSpec::create(1, 0, 1), &Doubling),
+            Ok(vec![0])
+        );
+        assert_eq!(
+            find_win_counts(&DeckSpec::create(1, 0, 2), &Doubling),
+            Err(GenerateError::Unreachable)
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+pub mod generator;
+pub mod scoring;
+use scoring::Scoring;
+
+/// What to do when a card wins more copies than there are cards after it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    /// Only hand out copies of the cards that exist.
+    Clamp,
+    /// Stop counting and report the card.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CountError {
+    /// Card `card` (by position, starting at 1) won copies of `win_count`
+    /// following cards, but only `remaining` cards follow it.
+    OutOfRange {
+        card: usize,
+        win_count: usize,
+        remaining: usize,
+    },
+    /// The number of copies of card `card` doesn't fit in a `u64`.
+    Overflow { card: usize },
+    /// The total number of cards doesn't fit in a `u64`.
+    TotalOverflow,
+}
+
+/// How many copies of each card were won, and where they came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cascade {
+    /// The number of copies of each card, including the original.
+    pub counts: Vec<u64>,
+    /// For each card, the earlier cards (by index) that won copies of it and
+    /// how many copies each one won.
+    pub sources: Vec<Vec<(usize, u64)>>,
+}
+
+/// Something wrong with a single card, or with its place in the deck.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    MissingColon,
+    MissingBar,
+    InvalidId(String),
+    InvalidNumber(String),
+    DuplicateWinningNumber(u32),
+    DuplicatePlayerNumber(u32),
+    /// The id was already used by the card on line `first_line`.
+    DuplicateId {
+        id: u32,
+        first_line: usize,
+    },
+    /// `expected` is a `u64` since the card after `u32::MAX` is still
+    /// expected to be one more.
+    NonSequentialId {
+        expected: u64,
+        found: u32,
+    },
+}
+
+/// A [`CardError`] along with the line (starting at 1) it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckError {
+    pub line: usize,
+    pub error: CardError,
+}
+
+#[derive(Debug)]
+pub struct ScratchCard {
+    id: u32,
+    winning_nums: HashSet<u32>,
+    player_nums: HashSet<u32>,
+}
+
+/// Parses every card in `input`, checking that no card repeats a number and
+/// that the card ids count up from 1. Every problem found is reported, not
+/// just the first.
+pub fn parse_deck(input: &str) -> Result<Vec<ScratchCard>, Vec<DeckError>> {
+    let mut cards = vec![];
+    let mut errors = vec![];
+    let mut seen_ids = HashMap::new();
+    let mut expected_id = 1u64;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let mut report = |error| {
+            errors.push(DeckError {
+                line: line_number,
+                error,
+            })
+        };
+
+        let id = match parse_id(line) {
+            Ok(id) => id,
+            Err(error) => {
+                report(error);
+                expected_id += 1;
+                continue;
+            }
+        };
+
+        if let Some(&first_line) = seen_ids.get(&id) {
+            report(CardError::DuplicateId { id, first_line });
+        } else {
+            if u64::from(id) != expected_id {
+                report(CardError::NonSequentialId {
+                    expected: expected_id,
+                    found: id,
+                });
+            }
+
+            seen_ids.insert(id, line_number);
+        }
+
+        expected_id = u64::from(id) + 1;
+
+        match ScratchCard::create(line) {
+            Ok(card) => cards.push(card),
+            Err(error) => report(error),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(cards)
+    } else {
+        Err(errors)
+    }
+}
+
+/// The part 1 answer, or `None` if it doesn't fit in a `u64`.
+pub fn get_total_points(cards: &[ScratchCard], scoring: &dyn Scoring) -> Option<u64> {
+    cards.iter().try_fold(0u64, |total, card| {
+        total.checked_add(card.get_points(scoring))
+    })
+}
+
+pub fn get_total_scratchcard_count(
+    cards: &[ScratchCard],
+    policy: OutOfRangePolicy,
+) -> Result<u64, CountError> {
+    let cascade = get_scratchcard_cascade(cards, policy)?;
+
+    cascade
+        .counts
+        .iter()
+        .try_fold(0u64, |total, &count| total.checked_add(count))
+        .ok_or(CountError::TotalOverflow)
+}
+
+pub fn get_scratchcard_cascade(
+    cards: &[ScratchCard],
+    policy: OutOfRangePolicy,
+) -> Result<Cascade, CountError> {
+    let mut counts = vec![1u64; cards.len()];
+    let mut sources = vec![vec![]; cards.len()];
+
+    for i in 0..cards.len() {
+        let win_count = cards[i].get_win_count();
+        let remaining = cards.len() - i - 1;
+
+        if win_count > remaining && policy == OutOfRangePolicy::Error {
+            return Err(CountError::OutOfRange {
+                card: i + 1,
+                win_count,
+                remaining,
+            });
+        }
+
+        for j in i + 1..=i + win_count.min(remaining) {
+            counts[j] = counts[j]
+                .checked_add(counts[i])
+                .ok_or(CountError::Overflow { card: j + 1 })?;
+
+            sources[j].push((i, counts[i]));
+        }
+    }
+
+    Ok(Cascade { counts, sources })
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountError::OutOfRange { card, win_count, remaining } => write!(
+                f,
+                "Card {card} wins copies of the next {win_count} cards, but only {remaining} follow it"
+            ),
+            CountError::Overflow { card } => write!(f, "The copies of card {card} don't fit in a u64"),
+            CountError::TotalOverflow => write!(f, "The total number of cards doesn't fit in a u64"),
+        }
+    }
+}
+
+impl std::error::Error for CountError {}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::MissingColon => write!(f, "Expected `:` after the card id"),
+            CardError::MissingBar => {
+                write!(f, "Expected `|` between the winning and player numbers")
+            }
+            CardError::InvalidId(id) => write!(f, "`{id}` isn't a valid card id"),
+            CardError::InvalidNumber(num) => write!(f, "`{num}` isn't a valid number"),
+            CardError::DuplicateWinningNumber(num) => {
+                write!(f, "Winning number {num} appears more than once")
+            }
+            CardError::DuplicatePlayerNumber(num) => {
+                write!(f, "Player number {num} appears more than once")
+            }
+            CardError::DuplicateId { id, first_line } => {
+                write!(f, "Card {id} was already used on line {first_line}")
+            }
+            CardError::NonSequentialId { expected, found } => {
+                write!(f, "Expected card {expected} but found card {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for DeckError {}
+
+impl ScratchCard {
+    /// Parses a single card. This only checks the card itself; see
+    /// [`parse_deck`] for checking the card ids.
+    pub fn create(input: &str) -> Result<Self, CardError> {
+        let id = parse_id(input)?;
+        let (_, rest) = input.split_once(':').ok_or(CardError::MissingColon)?;
+        let (winning_nums_part, player_nums_part) =
+            rest.split_once('|').ok_or(CardError::MissingBar)?;
+
+        let winning_nums = parse_nums(winning_nums_part, CardError::DuplicateWinningNumber)?;
+        let player_nums = parse_nums(player_nums_part, CardError::DuplicatePlayerNumber)?;
+
+        Ok(Self {
+            id,
+            winning_nums,
+            player_nums,
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn get_win_count(&self) -> usize {
+        self.player_nums.intersection(&self.winning_nums).count()
+    }
+
+    pub fn get_points(&self, scoring: &dyn Scoring) -> u64 {
+        scoring.score(self.get_win_count())
+    }
+}
+
+fn parse_id(input: &str) -> Result<u32, CardError> {
+    let (id_part, _) = input.split_once(':').ok_or(CardError::MissingColon)?;
+    let id_part = id_part.trim_start_matches("Card").trim();
+
+    id_part
+        .parse()
+        .map_err(|_| CardError::InvalidId(id_part.to_string()))
+}
+
+fn parse_nums(input: &str, duplicate: fn(u32) -> CardError) -> Result<HashSet<u32>, CardError> {
+    let mut nums = HashSet::new();
+
+    for num_str in input.split_whitespace() {
+        let num = num_str
+            .parse()
+            .map_err(|_| CardError::InvalidNumber(num_str.to_string()))?;
+        if !nums.insert(num) {
+            return Err(duplicate(num));
+        }
+    }
+
+    Ok(nums)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deck where card `i` (from 0) wins `win_counts[i]` times.
+    fn deck(win_counts: &[usize]) -> Vec<ScratchCard> {
+        let input = win_counts
+            .iter()
+            .enumerate()
+            .map(|(i, &win_count)| {
+                let nums = (1..=win_count)
+                    .map(|num| num.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("Card {}: {nums} | {nums}", i + 1)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        parse_deck(&input).unwrap()
+    }
+
+    fn deck_errors(input: &str) -> Vec<(usize, CardError)> {
+        parse_deck(input)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|err| (err.line, err.error))
+            .collect()
+    }
+
+    #[test]
+    fn card_ids() {
+        let non_sequential = |expected, found| CardError::NonSequentialId { expected, found };
+
+        let errors = deck_errors("Card 1: 1 | 2\nCard 3: 1 | 2\nCard 1: 1 | 2\nCard 2: 1 | 2");
+        let duplicate = CardError::DuplicateId {
+            id: 1,
+            first_line: 1,
+        };
+        assert_eq!(errors, [(2, non_sequential(2, 3)), (3, duplicate)]);
+
+        let errors = deck_errors("Card 4294967295: 1 | 2\nCard 1: 1 | 2");
+        assert_eq!(
+            errors,
+            [
+                (1, non_sequential(1, u32::MAX)),
+                (2, non_sequential(1 << 32, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_numbers() {
+        let errors = deck_errors("Card 1: 5 6 5 | 1 2\nCard 2: 1 2 | 7 8 7");
+        assert_eq!(
+            errors,
+            [
+                (1, CardError::DuplicateWinningNumber(5)),
+                (2, CardError::DuplicatePlayerNumber(7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn winning_past_the_end() {
+        let cards = deck(&[2, 1, 2]);
+
+        assert_eq!(
+            get_scratchcard_cascade(&cards, OutOfRangePolicy::Error),
+            Err(CountError::OutOfRange {
+                card: 3,
+                win_count: 2,
+                remaining: 0,
+            })
+        );
+
+        let cascade = get_scratchcard_cascade(&cards, OutOfRangePolicy::Clamp).unwrap();
+        assert_eq!(cascade.counts, [1, 2, 4]);
+        assert_eq!(
+            cascade.sources,
+            [vec![], vec![(0, 1)], vec![(0, 1), (1, 2)]]
+        );
+        assert_eq!(
+            get_total_scratchcard_count(&cards, OutOfRangePolicy::Clamp),
+            Ok(7)
+        );
+    }
+
+    #[test]
+    fn copies_overflow() {
+        // Every card wins a copy of every card after it, so card `i` ends up
+        // with 2^i copies.
+        let cards = deck(&(0..65).map(|i| 64 - i).collect::<Vec<_>>());
+        assert_eq!(
+            get_scratchcard_cascade(&cards, OutOfRangePolicy::Error),
+            Err(CountError::Overflow { card: 65 })
+        );
+
+        let cards = deck(&(0..64).map(|i| 63 - i).collect::<Vec<_>>());
+        let cascade = get_scratchcard_cascade(&cards, OutOfRangePolicy::Error).unwrap();
+        assert_eq!(cascade.counts[63], 1 << 63);
+        assert_eq!(
+            get_total_scratchcard_count(&cards, OutOfRangePolicy::Error),
+            Ok(u64::MAX)
+        );
+    }
+}
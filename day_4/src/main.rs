@@ -1,93 +1,110 @@
-use std::fs;
+use std::{env, fs, process};
 
-fn main() {
-    let input = fs::read_to_string("input.txt").expect("Couldn't find input file");
-
-    let cards = input.lines()
-        .map(ScratchCard::create)
-        .collect::<Vec<_>>();
-
-    let answer = get_total_points(&cards);
-    println!("Part 1 answer: {answer}");
-
-    let answer = get_total_scratchcard_count(&cards);
-    println!("Part 2 answer: {answer}");
-}
-
-fn get_total_points(cards: &Vec<ScratchCard>) -> u32 {
-    cards.iter()
-        .map(ScratchCard::get_points)
-        .sum()
-}
+use day_4::{
+    generator::{self, DeckSpec},
+    scoring::{self, Doubling, Scoring},
+    Cascade, OutOfRangePolicy,
+};
 
-fn get_total_scratchcard_count(cards: &Vec<ScratchCard>) -> u32 {
-    let mut counts = cards.iter().map(|_| 1).collect::<Vec<_>>();
-
-    for i in 0..cards.len() {
-        let win_count = cards[i].get_win_count();
-        for j in 1..=win_count {
-            counts[i + j] += counts[i];
+fn main() {
+    let mut policy = OutOfRangePolicy::Error;
+    let mut trace = false;
+    let mut generate = None;
+    let mut scoring: Box<dyn Scoring> = Box::new(Doubling);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out-of-range" => {
+                policy = match args.next().as_deref() {
+                    Some("clamp") => OutOfRangePolicy::Clamp,
+                    Some("error") => OutOfRangePolicy::Error,
+                    _ => usage(),
+                }
+            }
+            "--scoring" => {
+                scoring = args
+                    .next()
+                    .as_deref()
+                    .and_then(scoring::parse)
+                    .unwrap_or_else(|| usage())
+            }
+            "--trace" => trace = true,
+            "--generate" => {
+                let mut next_num = || {
+                    args.next()
+                        .and_then(|arg| arg.parse().ok())
+                        .unwrap_or_else(|| usage())
+                };
+                generate = Some(DeckSpec::create(
+                    next_num() as usize,
+                    next_num(),
+                    next_num(),
+                ));
+            }
+            _ => usage(),
         }
     }
 
-    counts.iter().sum()
-}
-
-#[derive(Debug)]
-struct ScratchCard {
-    _id: u32,
-    winning_nums: Vec<u32>,
-    player_nums: Vec<u32>,
-}
-
-impl ScratchCard {
-    fn create(input: &str) -> Self {
-        let parts = input.split(":").collect::<Vec<_>>();
-        let id_part = parts.first().unwrap();
-        let rest = parts.last().unwrap();
-
-        let id = id_part.trim_start_matches("Card").trim().parse().unwrap();
+    if let Some(spec) = generate {
+        match generator::generate(&spec, scoring.as_ref()) {
+            Ok(deck) => print!("{deck}"),
+            Err(err) => fail(err),
+        }
+        return;
+    }
 
-        let parts = rest.split("|").collect::<Vec<_>>();
-        let winning_nums_part = parts.first().unwrap().trim();
-        let player_nums_part = parts.last().unwrap().trim();
+    let input = fs::read_to_string("input.txt").expect("Couldn't find input file");
 
-        let winning_nums = parse_nums(winning_nums_part);
-        let player_nums = parse_nums(player_nums_part);
+    let cards = day_4::parse_deck(&input).unwrap_or_else(|errors| {
+        for error in errors {
+            eprintln!("{error}");
+        }
+        process::exit(1);
+    });
 
-        Self {
-            _id: id,
-            winning_nums,
-            player_nums,
+    if trace {
+        match day_4::get_scratchcard_cascade(&cards, policy) {
+            Ok(cascade) => print_trace(&cascade),
+            Err(err) => fail(err),
         }
+        return;
     }
 
-    fn get_win_count(&self) -> usize {
-        self.player_nums.iter()
-            .filter(|num| self.winning_nums.contains(num))
-            .count()
+    match day_4::get_total_points(&cards, scoring.as_ref()) {
+        Some(answer) => println!("Part 1 answer: {answer}"),
+        None => fail("The total points don't fit in a u64"),
     }
 
-    fn get_points(&self) -> u32 {
-        let mut points = 0;
-
-        for winning_num in self.winning_nums.iter() {
-            if self.player_nums.contains(winning_num) {
-                if points == 0 {
-                    points = 1;
-                } else {
-                    points *= 2;
-                }
-            }
-        }
+    match day_4::get_total_scratchcard_count(&cards, policy) {
+        Ok(answer) => println!("Part 2 answer: {answer}"),
+        Err(err) => fail(err),
+    }
+}
 
-        points
+fn print_trace(cascade: &Cascade) {
+    for (i, (count, sources)) in cascade
+        .counts
+        .iter()
+        .zip(cascade.sources.iter())
+        .enumerate()
+    {
+        let sources = sources
+            .iter()
+            .map(|(source, copies)| format!(", {copies} from card {}", source + 1))
+            .collect::<String>();
+
+        println!("Card {}: {count} copies (1 original{sources})", i + 1);
     }
 }
 
-fn parse_nums(input: &str) -> Vec<u32> {
-    input
-        .split(" ")
-        .filter_map(|num_str| num_str.parse().ok())
-        .collect()
+fn usage() -> ! {
+    eprintln!("Usage: day_4 [--out-of-range <clamp|error>] [--scoring <doubling|linear|fibonacci|table:N,...>] [--trace]");
+    eprintln!("       day_4 [--scoring <...>] --generate <deck size> <points> <card count>");
+    process::exit(2);
+}
+
+fn fail(err: impl std::fmt::Display) -> ! {
+    eprintln!("{err}");
+    process::exit(1);
 }
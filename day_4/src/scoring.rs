@@ -0,0 +1,109 @@
+//! The ways a scratchcard can turn its matching numbers into points.
+
+pub trait Scoring {
+    /// The points for a card with `win_count` matching numbers, saturating
+    /// at `u64::MAX` rather than overflowing.
+    fn score(&self, win_count: usize) -> u64;
+}
+
+/// The puzzle's scoring: 1 point for the first match, then doubled for each
+/// match after that.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Doubling;
+
+/// 1 point per match.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Linear;
+
+/// 1 point for the first match and 2 for the second, then each match is
+/// worth the sum of the previous two: 1, 2, 3, 5, 8, ...
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Fibonacci;
+
+/// Points looked up by match count, starting from zero matches. Counts past
+/// the end of the table get its last entry.
+#[derive(Debug, Clone)]
+pub struct Table(pub Vec<u64>);
+
+impl Scoring for Doubling {
+    fn score(&self, win_count: usize) -> u64 {
+        match win_count {
+            0 => 0,
+            n => u32::try_from(n - 1)
+                .ok()
+                .and_then(|exponent| 2u64.checked_pow(exponent))
+                .unwrap_or(u64::MAX),
+        }
+    }
+}
+
+impl Scoring for Linear {
+    fn score(&self, win_count: usize) -> u64 {
+        win_count as u64
+    }
+}
+
+impl Scoring for Fibonacci {
+    fn score(&self, win_count: usize) -> u64 {
+        if win_count == 0 {
+            return 0;
+        }
+
+        let (mut a, mut b) = (1u64, 2u64);
+        for _ in 1..win_count {
+            if a == u64::MAX {
+                break;
+            }
+            (a, b) = (b, a.saturating_add(b));
+        }
+
+        a
+    }
+}
+
+impl Scoring for Table {
+    fn score(&self, win_count: usize) -> u64 {
+        self.0
+            .get(win_count)
+            .or(self.0.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Parses a scoring scheme by name: `doubling`, `linear`, `fibonacci`, or
+/// `table:` followed by comma separated points, e.g. `table:0,1,3,6`.
+pub fn parse(input: &str) -> Option<Box<dyn Scoring>> {
+    match input {
+        "doubling" => Some(Box::new(Doubling)),
+        "linear" => Some(Box::new(Linear)),
+        "fibonacci" => Some(Box::new(Fibonacci)),
+        _ => {
+            let table = input
+                .strip_prefix("table:")?
+                .split(',')
+                .map(|points| points.trim().parse().ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            Some(Box::new(Table(table)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_saturate() {
+        assert_eq!(Doubling.score(64), 1 << 63);
+        assert_eq!(Doubling.score(65), u64::MAX);
+        assert_eq!(Doubling.score(70), u64::MAX);
+
+        assert_eq!(Fibonacci.score(5), 8);
+        assert_eq!(Fibonacci.score(91), 7540113804746346429);
+        assert_eq!(Fibonacci.score(92), 12200160415121876738);
+        assert_eq!(Fibonacci.score(93), u64::MAX);
+        assert_eq!(Fibonacci.score(1000), u64::MAX);
+    }
+}
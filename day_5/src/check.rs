@@ -0,0 +1,249 @@
+//! Checks an almanac for rows that [`Garden::create`](crate::Garden::create)
+//! would quietly skip, cut short or shadow.
+//!
+//! This works on the raw text rather than on a parsed [`Garden`](crate::Garden),
+//! since parsing already throws away the rows it can't use.
+
+use std::{fmt, ops::Range};
+
+use crate::{is_blank, parse_heading, parse_row, parse_seeds, range_map::RangeEntry};
+
+/// Something worth pointing out about the line `line` (starting at 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub line: usize,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// The first line isn't `seeds:` followed by pairs of numbers.
+    MalformedSeeds,
+    /// The seed pair `start length` runs past `u64::MAX`.
+    SeedOverflow { start: u64, length: u64 },
+    /// A line that should be an `x-to-y map:` heading isn't one.
+    MalformedHeading(String),
+    /// A row that isn't three numbers. It gets skipped.
+    MalformedRow(String),
+    /// `source_start + length` or `dest_start + length` doesn't fit in a
+    /// `u64`. The row gets cut short.
+    Overflow { map: String },
+    /// This row's source range overlaps the one on `other_line`, so the
+    /// values in `overlap` depend on which row comes first.
+    OverlappingSources {
+        map: String,
+        other_line: usize,
+        overlap: Range<u64>,
+    },
+    /// This row's destination range overlaps the one on `other_line`, so
+    /// the values in `overlap` can't be mapped backwards exactly.
+    OverlappingDestinations {
+        map: String,
+        other_line: usize,
+        overlap: Range<u64>,
+    },
+    /// Values between this row and the next one (by source) that no row
+    /// covers, so they map to themselves.
+    Gap { map: String, gap: Range<u64> },
+}
+
+impl Issue {
+    /// Whether this makes the answer depend on details of the parser, as
+    /// opposed to being something the puzzle allows.
+    pub fn is_error(&self) -> bool {
+        !matches!(self.kind, IssueKind::Gap { .. })
+    }
+}
+
+/// Every issue in `input`, in line order.
+pub fn check(input: &str) -> Vec<Issue> {
+    let mut issues = vec![];
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    let seeds = lines.next().and_then(|(_, line)| parse_seeds(line));
+    match seeds {
+        Some(seeds) if !seeds.is_empty() && seeds.len() % 2 == 0 => {
+            for pair in seeds.chunks(2) {
+                let (start, length) = (pair[0], pair[1]);
+                if start.checked_add(length).is_none() {
+                    issues.push(Issue {
+                        line: 1,
+                        kind: IssueKind::SeedOverflow { start, length },
+                    });
+                }
+            }
+        }
+        _ => issues.push(Issue {
+            line: 1,
+            kind: IssueKind::MalformedSeeds,
+        }),
+    }
+
+    let mut map: Option<String> = None;
+    let mut rows = vec![];
+
+    for (line_number, line) in lines {
+        if is_blank(line) {
+            if let Some(map) = map.take() {
+                check_map(&map, &rows, &mut issues);
+            }
+            rows.clear();
+            continue;
+        }
+
+        let line = line.trim();
+
+        if map.is_none() {
+            let name = match parse_heading(line) {
+                Some((source, destination)) => format!("{source}-to-{destination}"),
+                None => {
+                    issues.push(Issue {
+                        line: line_number,
+                        kind: IssueKind::MalformedHeading(line.to_string()),
+                    });
+
+                    // Keep checking the rows under a bad heading, named as
+                    // best we can.
+                    line.to_string()
+                }
+            };
+
+            map = Some(name);
+            continue;
+        }
+
+        match parse_row(line) {
+            Some(entry) => rows.push((line_number, entry)),
+            None => issues.push(Issue {
+                line: line_number,
+                kind: IssueKind::MalformedRow(line.to_string()),
+            }),
+        }
+    }
+
+    if let Some(map) = map {
+        check_map(&map, &rows, &mut issues);
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+fn check_map(map: &str, rows: &[(usize, RangeEntry)], issues: &mut Vec<Issue>) {
+    let mut ranges = vec![];
+
+    for &(line, entry) in rows {
+        let source_end = entry.source_start.checked_add(entry.length);
+        let dest_end = entry.dest_start.checked_add(entry.length);
+
+        match (source_end, dest_end) {
+            (Some(source_end), Some(dest_end)) => {
+                ranges.push((
+                    line,
+                    entry.source_start..source_end,
+                    entry.dest_start..dest_end,
+                ));
+            }
+            _ => issues.push(Issue {
+                line,
+                kind: IssueKind::Overflow {
+                    map: map.to_string(),
+                },
+            }),
+        }
+    }
+
+    for (i, (line, source, dest)) in ranges.iter().enumerate() {
+        for (other_line, other_source, other_dest) in ranges[..i].iter() {
+            if let Some(overlap) = overlap(source, other_source) {
+                issues.push(Issue {
+                    line: *line,
+                    kind: IssueKind::OverlappingSources {
+                        map: map.to_string(),
+                        other_line: *other_line,
+                        overlap,
+                    },
+                });
+            }
+
+            if let Some(overlap) = overlap(dest, other_dest) {
+                issues.push(Issue {
+                    line: *line,
+                    kind: IssueKind::OverlappingDestinations {
+                        map: map.to_string(),
+                        other_line: *other_line,
+                        overlap,
+                    },
+                });
+            }
+        }
+    }
+
+    ranges.sort_by_key(|(_, source, _)| source.start);
+
+    let mut covered_to = None;
+    for (line, source, _) in ranges.iter() {
+        if let Some(end) = covered_to {
+            if end < source.start {
+                issues.push(Issue {
+                    line: *line,
+                    kind: IssueKind::Gap {
+                        map: map.to_string(),
+                        gap: end..source.start,
+                    },
+                });
+            }
+        }
+
+        covered_to = Some(covered_to.map_or(source.end, |end: u64| end.max(source.end)));
+    }
+}
+
+fn overlap(a: &Range<u64>, b: &Range<u64>) -> Option<Range<u64>> {
+    let overlap = a.start.max(b.start)..a.end.min(b.end);
+    (!overlap.is_empty()).then_some(overlap)
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = if self.is_error() { "error" } else { "warning" };
+        write!(f, "{level}: line {}: ", self.line)?;
+
+        match &self.kind {
+            IssueKind::MalformedSeeds => {
+                write!(f, "Expected `seeds:` followed by pairs of numbers")
+            }
+            IssueKind::SeedOverflow { start, length } => {
+                write!(f, "The seeds {start} {length} run past {}", u64::MAX)
+            }
+            IssueKind::MalformedHeading(line) => {
+                write!(f, "Expected an `x-to-y map:` heading but found `{line}`")
+            }
+            IssueKind::MalformedRow(row) => write!(f, "Expected three numbers but found `{row}`"),
+            IssueKind::Overflow { map } => write!(f, "This {map} row runs past {}", u64::MAX),
+            IssueKind::OverlappingSources {
+                map,
+                other_line,
+                overlap,
+            } => write!(
+                f,
+                "This {map} row's source range overlaps line {other_line} at {}..{}",
+                overlap.start, overlap.end
+            ),
+            IssueKind::OverlappingDestinations {
+                map,
+                other_line,
+                overlap,
+            } => write!(
+                f,
+                "This {map} row's destination range overlaps line {other_line} at {}..{}",
+                overlap.start, overlap.end
+            ),
+            IssueKind::Gap { map, gap } => write!(
+                f,
+                "No {map} row covers {}..{} before this one",
+                gap.start, gap.end
+            ),
+        }
+    }
+}
@@ -0,0 +1,591 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    ops::Range,
+};
+
+pub mod check;
+pub mod preimage;
+pub mod range_map;
+pub mod trace;
+
+use range_map::{RangeEntry, RangeMap};
+
+macro_rules! assert_unreachable {
+    () => {
+        panic!("Unreachable code was reached!")
+    };
+}
+
+#[derive(Debug)]
+pub struct Garden {
+    pub seeds: Vec<u64>,
+    maps: Vec<CategoryMap>,
+}
+
+/// One `x-to-y map:` section of the almanac.
+#[derive(Debug)]
+pub struct CategoryMap {
+    pub source: String,
+    pub destination: String,
+    /// Maps `source` values to `destination` values.
+    pub map: RangeMap,
+    /// Maps `destination` values back to `source` values. Where destination
+    /// ranges overlap, the earlier row wins.
+    backwards: RangeMap,
+}
+
+/// Which way a map is used: from its source category to its destination
+/// category, or back again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forwards,
+    Backwards,
+}
+
+/// Why an almanac couldn't be parsed. Rows that aren't three numbers aren't
+/// an error: they get skipped, and [`check`] points them out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The first line isn't `seeds:` followed by numbers.
+    MalformedSeeds,
+    /// Line `line` (starting at 1) should be an `x-to-y map:` heading.
+    MalformedHeading { line: usize, heading: String },
+}
+
+/// Why the seeds can't be read as pairs of `start length`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedError {
+    /// No pair has any seeds in it, e.g. because there's only a lone start.
+    NoSeeds,
+    /// The pair `start length` runs past `u64::MAX`.
+    Overflow { start: u64, length: u64 },
+}
+
+/// One step of a path between two categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// The index of the map in [`Garden::maps`].
+    pub map: usize,
+    pub direction: Direction,
+}
+
+impl Garden {
+    pub fn create(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines().zip(1..);
+
+        let seeds = lines
+            .next()
+            .and_then(|(line, _)| parse_seeds(line))
+            .ok_or(ParseError::MalformedSeeds)?;
+        let mut maps = vec![];
+
+        while let Some((heading, line)) = lines.find(|(line, _)| !is_blank(line)) {
+            let (source, destination) =
+                parse_heading(heading).ok_or_else(|| ParseError::MalformedHeading {
+                    line,
+                    heading: heading.trim().to_string(),
+                })?;
+
+            // Rows that aren't three numbers get skipped, and `check` points
+            // them out.
+            let rows = lines
+                .by_ref()
+                .map(|(line, _)| line)
+                .take_while(|line| !is_blank(line))
+                .filter_map(parse_row)
+                .collect::<Vec<_>>();
+
+            maps.push(CategoryMap {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                map: RangeMap::create(rows.iter().copied()),
+                backwards: RangeMap::create(rows.iter().map(RangeEntry::swapped)),
+            });
+        }
+
+        Ok(Garden { seeds, maps })
+    }
+
+    pub fn maps(&self) -> &[CategoryMap] {
+        &self.maps
+    }
+
+    /// Every category named by a map heading, in the order they first appear.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories = vec![];
+
+        for map in self.maps.iter() {
+            for category in [&map.source, &map.destination] {
+                if !categories.contains(&category.as_str()) {
+                    categories.push(category.as_str());
+                }
+            }
+        }
+
+        categories
+    }
+
+    /// The shortest chain of maps leading from category `from` to category
+    /// `to`, using maps backwards where needed. Returns `None` if the two
+    /// categories aren't connected.
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<Step>> {
+        let mut came_from: HashMap<&str, Option<(&str, Step)>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+
+            for (i, map) in self.maps.iter().enumerate() {
+                let (next, direction) = if map.source == category {
+                    (map.destination.as_str(), Direction::Forwards)
+                } else if map.destination == category {
+                    (map.source.as_str(), Direction::Backwards)
+                } else {
+                    continue;
+                };
+
+                if !came_from.contains_key(next) {
+                    came_from.insert(next, Some((category, Step { map: i, direction })));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut category = to;
+        while let Some((previous, step)) = came_from.get(category)?.as_ref() {
+            path.push(*step);
+            category = previous;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Converts `value` from category `from` to category `to`. Returns `None`
+    /// if the two categories aren't connected.
+    pub fn convert(&self, value: u64, from: &str, to: &str) -> Option<u64> {
+        let path = self.path(from, to)?;
+
+        Some(path.iter().fold(value, |value, step| {
+            self.maps[step.map].get(step.direction).get(value)
+        }))
+    }
+
+    /// Like [`Garden::convert`], but for whole ranges of values.
+    pub fn convert_ranges(
+        &self,
+        ranges: &[Range<u64>],
+        from: &str,
+        to: &str,
+    ) -> Option<Vec<Range<u64>>> {
+        let path = self.path(from, to)?;
+
+        Some(path.iter().fold(ranges.to_vec(), |ranges, step| {
+            self.maps[step.map].get(step.direction).get_ranges(&ranges)
+        }))
+    }
+
+    /// Collapses every map between category `from` and category `to` into a
+    /// single map. Returns `None` if the two categories aren't connected.
+    pub fn compose(&self, from: &str, to: &str) -> Option<RangeMap> {
+        let path = self.path(from, to)?;
+
+        Some(path.iter().fold(RangeMap::default(), |composed, step| {
+            composed.compose(self.maps[step.map].get(step.direction))
+        }))
+    }
+
+    pub fn get_nearest_location_discrete(&self) -> u64 {
+        self.get_nearest_location(&self.seeds)
+    }
+
+    /// Treats the seeds as pairs of `start length` and pushes each of those
+    /// ranges through the maps whole, splitting them up wherever a map does.
+    pub fn get_nearest_location_ranged(&self) -> Result<u64, SeedError> {
+        self.convert_ranges(&self.get_seed_ranges()?, "seed", "location")
+            .expect("Expected the maps to lead from seed to location")
+            .iter()
+            .filter(|range| !range.is_empty())
+            .map(|range| range.start)
+            .min()
+            .ok_or(SeedError::NoSeeds)
+    }
+
+    /// Walks every location upwards from zero, back through the maps, until it
+    /// reaches one of the seeds. This can take billions of steps, so it's only
+    /// useful for cross-checking [`Garden::get_nearest_location_ranged`].
+    pub fn get_nearest_location_brute_force(&self) -> u64 {
+        let path = self
+            .path("location", "seed")
+            .expect("Expected the maps to lead from location to seed");
+
+        for location in 0..=u64::MAX {
+            let seed = path.iter().fold(location, |value, step| {
+                self.maps[step.map].get(step.direction).get(value)
+            });
+
+            if self.has_seed(seed) {
+                return location;
+            }
+        }
+
+        assert_unreachable!();
+    }
+
+    fn get_nearest_location(&self, seeds: &[u64]) -> u64 {
+        let mut locations = seeds
+            .iter()
+            .map(|&seed| self.get_location(seed))
+            .collect::<Vec<_>>();
+
+        locations.sort();
+        locations[0]
+    }
+
+    fn get_location(&self, seed: u64) -> u64 {
+        self.convert(seed, "seed", "location")
+            .expect("Expected the maps to lead from seed to location")
+    }
+
+    /// The seeds read as pairs of `start length`. A lone start at the end is
+    /// ignored.
+    fn get_seed_ranges(&self) -> Result<Vec<Range<u64>>, SeedError> {
+        self.seeds
+            .chunks_exact(2)
+            .map(|pair| {
+                let (start, length) = (pair[0], pair[1]);
+                let end = start
+                    .checked_add(length)
+                    .ok_or(SeedError::Overflow { start, length })?;
+                Ok(start..end)
+            })
+            .collect()
+    }
+
+    fn has_seed(&self, seed: u64) -> bool {
+        self.seeds
+            .chunks_exact(2)
+            .any(|pair| seed >= pair[0] && seed - pair[0] < pair[1])
+    }
+}
+
+// `Garden::create` and `check` share these, so that they agree on what every
+// line means.
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// The numbers after `seeds:`.
+fn parse_seeds(line: &str) -> Option<Vec<u64>> {
+    line.trim()
+        .strip_prefix("seeds:")?
+        .split_whitespace()
+        .map(|seed| seed.parse().ok())
+        .collect()
+}
+
+/// The source and destination named by an `x-to-y map:` heading.
+fn parse_heading(line: &str) -> Option<(&str, &str)> {
+    line.trim().strip_suffix(" map:")?.split_once("-to-")
+}
+
+/// A `dest_start source_start length` row.
+fn parse_row(line: &str) -> Option<RangeEntry> {
+    let row = line
+        .split_whitespace()
+        .map(|num| num.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    match row[..] {
+        [dest_start, source_start, length] => Some(RangeEntry {
+            dest_start,
+            source_start,
+            length,
+        }),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MalformedSeeds => write!(f, "Expected `seeds:` followed by numbers"),
+            ParseError::MalformedHeading { line, heading } => write!(
+                f,
+                "Expected an `x-to-y map:` heading on line {line} but found `{heading}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::NoSeeds => write!(f, "Expected at least one `start length` seed pair"),
+            SeedError::Overflow { start, length } => {
+                write!(f, "The seeds {start} {length} run past {}", u64::MAX)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SeedError {}
+
+impl CategoryMap {
+    /// The name of the category that `direction` leads to.
+    pub fn target(&self, direction: Direction) -> &str {
+        match direction {
+            Direction::Forwards => &self.destination,
+            Direction::Backwards => &self.source,
+        }
+    }
+
+    fn get(&self, direction: Direction) -> &RangeMap {
+        match direction {
+            Direction::Forwards => &self.map,
+            Direction::Backwards => &self.backwards,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn example() {
+        let garden = Garden::create(EXAMPLE).unwrap();
+
+        assert_eq!(garden.get_nearest_location_discrete(), 35);
+        assert_eq!(garden.get_nearest_location_ranged(), Ok(46));
+    }
+
+    #[test]
+    fn parse_and_check_agree_on_whitespace() {
+        let input = "seeds: 1 1\n\nseed-to-location map:\n50 0 10 \n";
+        let garden = Garden::create(input).unwrap();
+        assert_eq!(garden.get_nearest_location_discrete(), 51);
+        assert_eq!(check::check(input), []);
+
+        let spaced = EXAMPLE.replacen("\n\n", "\n  \n", 2);
+        let garden = Garden::create(&spaced).unwrap();
+        assert_eq!(garden.maps().len(), 7);
+        assert_eq!(garden.get_nearest_location_discrete(), 35);
+        assert_eq!(garden.get_nearest_location_ranged(), Ok(46));
+        assert_eq!(check::check(&spaced), check::check(EXAMPLE));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let maps = EXAMPLE.split_once('\n').unwrap().1;
+
+        assert_eq!(
+            Garden::create(&format!("seeds: 79 x\n{maps}")).unwrap_err(),
+            ParseError::MalformedSeeds
+        );
+        assert_eq!(Garden::create("").unwrap_err(), ParseError::MalformedSeeds);
+        assert_eq!(
+            Garden::create("seeds: 79 14\n\nseed to soil:\n50 98 2\n").unwrap_err(),
+            ParseError::MalformedHeading {
+                line: 3,
+                heading: "seed to soil:".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn check_flags_seed_overflow() {
+        let maps = EXAMPLE.split_once('\n').unwrap().1;
+        let input = format!("seeds: 79 14 {} 2\n{maps}", u64::MAX);
+
+        let issues = check::check(&input);
+        assert!(issues.contains(&check::Issue {
+            line: 1,
+            kind: check::IssueKind::SeedOverflow {
+                start: u64::MAX,
+                length: 2
+            }
+        }));
+    }
+
+    #[test]
+    fn bad_seed_ranges() {
+        let maps = EXAMPLE.split_once('\n').unwrap().1;
+
+        let garden = Garden::create(&format!("seeds: 79\n{maps}")).unwrap();
+        assert_eq!(
+            garden.get_nearest_location_ranged(),
+            Err(SeedError::NoSeeds)
+        );
+
+        let garden = Garden::create(&format!("seeds: 79 0\n{maps}")).unwrap();
+        assert_eq!(
+            garden.get_nearest_location_ranged(),
+            Err(SeedError::NoSeeds)
+        );
+
+        let garden = Garden::create(&format!("seeds: 79 14 {} 2\n{maps}", u64::MAX)).unwrap();
+        assert_eq!(
+            garden.get_nearest_location_ranged(),
+            Err(SeedError::Overflow {
+                start: u64::MAX,
+                length: 2
+            })
+        );
+    }
+
+    #[test]
+    fn compose_matches_convert() {
+        let garden = Garden::create(EXAMPLE).unwrap();
+
+        for (from, to) in [
+            ("seed", "location"),
+            ("location", "seed"),
+            ("soil", "light"),
+        ] {
+            let composed = garden.compose(from, to).unwrap();
+
+            for value in 0..120 {
+                assert_eq!(
+                    Some(composed.get(value)),
+                    garden.convert(value, from, to),
+                    "{from} {value} to {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invert_round_trips() {
+        let garden = Garden::create(EXAMPLE).unwrap();
+        let map = garden.compose("seed", "location").unwrap();
+        let inverse = map.invert().unwrap();
+
+        assert_eq!(inverse.invert(), Some(map.clone()));
+        for value in 0..120 {
+            assert_eq!(inverse.get(map.get(value)), value);
+        }
+
+        // 10..15 and 0..5 both end up in 0..5.
+        let squashed = RangeMap::create([RangeEntry {
+            dest_start: 0,
+            source_start: 10,
+            length: 5,
+        }]);
+        assert_eq!(squashed.invert(), None);
+    }
+
+    #[test]
+    fn path_through_a_backwards_step() {
+        let garden = Garden::create(
+            "seeds: 1 2\n\nseed-to-soil map:\n10 0 5\n\nwater-to-soil map:\n10 20 5\n",
+        )
+        .unwrap();
+
+        let path = garden.path("seed", "water").unwrap();
+        assert_eq!(
+            path,
+            [
+                Step {
+                    map: 0,
+                    direction: Direction::Forwards
+                },
+                Step {
+                    map: 1,
+                    direction: Direction::Backwards
+                },
+            ]
+        );
+
+        assert_eq!(garden.convert(3, "seed", "water"), Some(23));
+        assert_eq!(garden.convert(23, "water", "seed"), Some(3));
+        assert_eq!(garden.convert(7, "seed", "water"), Some(7));
+        assert_eq!(garden.path("seed", "light"), None);
+    }
+
+    #[test]
+    fn seeds_for_example_locations() {
+        let garden = Garden::create(EXAMPLE).unwrap();
+        let intervals = garden.seeds_for_locations(40..50).unwrap();
+
+        let seeds = |has_seed| {
+            intervals
+                .iter()
+                .filter(|interval| interval.has_seed == has_seed)
+                .map(|interval| interval.seeds.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(seeds(true), vec![82..86]);
+        assert_eq!(seeds(false), [14..15, 19..22, 52..54]);
+
+        // The maps leave everything from 100 up alone, so that's far enough
+        // to look for seeds that were missed.
+        let expected = (0..200)
+            .filter(|&seed| (40..50).contains(&garden.get_location(seed)))
+            .collect::<Vec<_>>();
+        let found = intervals
+            .iter()
+            .flat_map(|interval| interval.seeds.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn ranged_matches_every_seed() {
+        let garden = Garden::create(EXAMPLE).unwrap();
+
+        let nearest = garden
+            .get_seed_ranges()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|seed| garden.convert(seed, "seed", "location").unwrap())
+            .min()
+            .unwrap();
+
+        assert_eq!(garden.get_nearest_location_ranged(), Ok(nearest));
+    }
+}
@@ -1,15 +1,204 @@
-use std::fs;
+use std::{env, fs, process};
 
-mod garden;
-use garden::Garden;
+use day_5::{check, Garden};
 
 fn main() {
+    let mut brute_force = false;
+    let mut check = false;
+    let mut convert = None;
+    let mut compose = None;
+    let mut trace = None;
+    let mut trace_csv = false;
+    let mut seeds_for = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "check" => check = true,
+            // This only ever checks day 5, but takes `--day 5` for anyone
+            // used to naming the day.
+            "--day" => {
+                if args.next().as_deref() != Some("5") {
+                    usage();
+                }
+            }
+            "--brute-force" => brute_force = true,
+            "--convert" => {
+                let (Some(from), Some(to), Some(value)) = (args.next(), args.next(), args.next())
+                else {
+                    usage();
+                };
+                let value: u64 = value.parse().unwrap_or_else(|_| usage());
+                convert = Some((from, to, value));
+            }
+            "--trace" => {
+                let (Some(from), Some(value)) = (args.next(), args.next()) else {
+                    usage();
+                };
+                let value: u64 = value.parse().unwrap_or_else(|_| usage());
+                trace = Some((from, value));
+            }
+            "--trace-csv" => trace_csv = true,
+            "--seeds-for" => {
+                let mut next_num = || {
+                    args.next()
+                        .and_then(|arg| arg.parse::<u64>().ok())
+                        .unwrap_or_else(|| usage())
+                };
+                seeds_for = Some(next_num()..next_num());
+            }
+            "--compose" => {
+                let (Some(from), Some(to)) = (args.next(), args.next()) else {
+                    usage();
+                };
+                compose = Some((from, to));
+            }
+            _ => usage(),
+        }
+    }
+
     let input = fs::read_to_string("input.txt").expect("Couldn't find input file");
-    let garden = Garden::create(&input);
+
+    if check {
+        print_issues(&input);
+        return;
+    }
+
+    let garden = Garden::create(&input).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    if let Some((from, to, value)) = convert {
+        print_conversion(&garden, &from, &to, value);
+        return;
+    }
+
+    if let Some((from, value)) = trace {
+        print_trace(&garden, &from, value);
+        return;
+    }
+
+    if trace_csv {
+        print!("{}", garden.trace_seeds_csv());
+        return;
+    }
+
+    if let Some(locations) = seeds_for {
+        let intervals = garden.seeds_for_locations(locations).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(1);
+        });
+
+        for interval in intervals {
+            let seeds = format!("{}..{}", interval.seeds.start, interval.seeds.end);
+            let has_seed = if interval.has_seed {
+                "has seed"
+            } else {
+                "no seed"
+            };
+            println!("{seeds:<24} {has_seed}");
+        }
+        return;
+    }
+
+    if let Some((from, to)) = compose {
+        let Some(map) = garden.compose(&from, &to) else {
+            eprintln!("There's no way to get from {from} to {to}");
+            process::exit(1);
+        };
+
+        println!("{from}-to-{to} map:");
+        print!("{map}");
+        return;
+    }
 
     let answer = garden.get_nearest_location_discrete();
     println!("Part 1 answer: {answer}");
 
-    let answer = garden.get_nearest_location_ranged();
+    let answer = garden.get_nearest_location_ranged().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
     println!("Part 2 answer: {answer}");
+
+    if brute_force {
+        let brute_force_answer = garden.get_nearest_location_brute_force();
+        println!("Part 2 answer (brute force): {brute_force_answer}");
+
+        if brute_force_answer != answer {
+            eprintln!("The brute force answer doesn't match!");
+            process::exit(1);
+        }
+    }
+}
+
+fn print_conversion(garden: &Garden, from: &str, to: &str, value: u64) {
+    let Some(path) = garden.path(from, to) else {
+        eprintln!("There's no way to get from {from} to {to}");
+        process::exit(1);
+    };
+
+    let mut route = from.to_string();
+    for step in path {
+        let map = &garden.maps()[step.map];
+        route.push_str(" -> ");
+        route.push_str(map.target(step.direction));
+    }
+
+    println!("{route}");
+    println!(
+        "{from} {value} is {to} {}",
+        garden.convert(value, from, to).unwrap()
+    );
+}
+
+/// Traces `value` to its location, or back to its seed if it's a location.
+fn print_trace(garden: &Garden, from: &str, value: u64) {
+    let to = if from == "location" {
+        "seed"
+    } else {
+        "location"
+    };
+
+    let Some(stages) = garden.trace(value, from, to) else {
+        eprintln!("There's no way to get from {from} to {to}");
+        process::exit(1);
+    };
+
+    for stage in stages {
+        match stage.row {
+            Some(row) => println!(
+                "{:<12} {:>12}  (row {} {} {})",
+                stage.category, stage.value, row.dest_start, row.source_start, row.length
+            ),
+            None => println!("{:<12} {:>12}", stage.category, stage.value),
+        }
+    }
+}
+
+fn print_issues(input: &str) {
+    let issues = check::check(input);
+    for issue in issues.iter() {
+        println!("{issue}");
+    }
+
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    let warnings = issues.len() - errors;
+    println!("{errors} errors, {warnings} warnings");
+
+    if errors > 0 {
+        process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: day_5 [--brute-force]");
+    eprintln!("       day_5 --convert <from> <to> <value>");
+    eprintln!("       day_5 --compose <from> <to>");
+    eprintln!("       day_5 --trace <seed|location> <value>");
+    eprintln!("       day_5 --trace-csv");
+    eprintln!("       day_5 --seeds-for <location start> <location end>");
+    eprintln!("       day_5 check [--day 5]");
+    process::exit(2);
 }
@@ -0,0 +1,76 @@
+//! Working backwards from a range of values to every value that ends up in
+//! it. Unlike [`Garden::convert`], this doesn't need the maps to be one to
+//! one, since it only ever runs them forwards.
+
+use std::ops::Range;
+
+use crate::{range_map, Garden, SeedError};
+
+/// A run of seeds that all end up in the locations asked about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedInterval {
+    pub seeds: Range<u64>,
+    /// Whether these seeds are in the seed ranges of the almanac (reading the
+    /// seeds as pairs of `start length`). Intervals are split up so that this
+    /// holds for either all of the seeds or none of them.
+    pub has_seed: bool,
+}
+
+impl Garden {
+    /// Every value in category `from` that ends up in one of `ranges` in
+    /// category `to`. Returns `None` if the two categories aren't connected.
+    pub fn preimage(&self, ranges: &[Range<u64>], from: &str, to: &str) -> Option<Vec<Range<u64>>> {
+        let path = self.path(from, to)?;
+
+        Some(path.iter().rev().fold(ranges.to_vec(), |ranges, step| {
+            self.maps[step.map].get(step.direction).preimage(&ranges)
+        }))
+    }
+
+    /// Every seed that ends up at a location in `locations`.
+    pub fn seeds_for_locations(
+        &self,
+        locations: Range<u64>,
+    ) -> Result<Vec<SeedInterval>, SeedError> {
+        let preimage = self
+            .preimage(&[locations], "seed", "location")
+            .expect("Expected the maps to lead from seed to location");
+
+        let mut seed_ranges = self.get_seed_ranges()?;
+        range_map::merge_ranges(&mut seed_ranges);
+
+        let mut intervals = vec![];
+        for seeds in preimage {
+            let mut start = seeds.start;
+
+            for seed_range in seed_ranges.iter() {
+                let overlap = start.max(seed_range.start)..seeds.end.min(seed_range.end);
+                if overlap.is_empty() {
+                    continue;
+                }
+
+                if start < overlap.start {
+                    intervals.push(SeedInterval {
+                        seeds: start..overlap.start,
+                        has_seed: false,
+                    });
+                }
+
+                start = overlap.end;
+                intervals.push(SeedInterval {
+                    seeds: overlap,
+                    has_seed: true,
+                });
+            }
+
+            if start < seeds.end {
+                intervals.push(SeedInterval {
+                    seeds: start..seeds.end,
+                    has_seed: false,
+                });
+            }
+        }
+
+        Ok(intervals)
+    }
+}
@@ -0,0 +1,269 @@
+//! A map between two categories, stored as sorted, non-overlapping ranges.
+//! Values that no range covers map to themselves.
+
+use std::{fmt, ops::Range};
+
+/// One `dest_start source_start length` row of an almanac map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeEntry {
+    pub dest_start: u64,
+    pub source_start: u64,
+    pub length: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// Sorted by `source_start`, with no two source ranges overlapping.
+    entries: Vec<RangeEntry>,
+}
+
+impl RangeEntry {
+    pub fn source(&self) -> Range<u64> {
+        self.source_start..self.source_start + self.length
+    }
+
+    pub fn dest(&self) -> Range<u64> {
+        self.dest_start..self.dest_start + self.length
+    }
+
+    /// The entry going the other way, from `dest` to `source`.
+    pub fn swapped(&self) -> Self {
+        RangeEntry {
+            dest_start: self.source_start,
+            source_start: self.dest_start,
+            length: self.length,
+        }
+    }
+
+    /// The part of this entry covering `source`, if any.
+    fn restrict(&self, source: Range<u64>) -> Option<RangeEntry> {
+        let start = source.start.max(self.source_start);
+        let end = source.end.min(self.source().end);
+
+        (start < end).then(|| RangeEntry {
+            dest_start: self.dest_start + (start - self.source_start),
+            source_start: start,
+            length: end - start,
+        })
+    }
+}
+
+impl RangeMap {
+    /// Builds a map from rows in almanac order. Where source ranges overlap,
+    /// the earlier row wins, like it does when searching the rows in order.
+    /// Rows that would run past `u64::MAX` are cut short.
+    pub fn create(rows: impl IntoIterator<Item = RangeEntry>) -> Self {
+        let mut map = RangeMap::default();
+
+        for row in rows {
+            let length = row
+                .length
+                .min(u64::MAX - row.source_start)
+                .min(u64::MAX - row.dest_start);
+
+            let row = RangeEntry { length, ..row };
+            let gaps = map.gaps_within(row.source());
+            let pieces = gaps.into_iter().filter_map(|gap| row.restrict(gap));
+
+            map.entries.extend(pieces);
+            map.entries.sort_by_key(|entry| entry.source_start);
+        }
+
+        map
+    }
+
+    pub fn entries(&self) -> &[RangeEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, value: u64) -> u64 {
+        match self.entry_for(value) {
+            Some(entry) => value - entry.source_start + entry.dest_start,
+            None => value,
+        }
+    }
+
+    /// Maps whole ranges of values, splitting them wherever the map does.
+    pub fn get_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        ranges
+            .iter()
+            .flat_map(|range| self.pieces(range.clone()))
+            .map(|piece| piece.dest())
+            .collect()
+    }
+
+    /// The map that does `self` and then `next`.
+    pub fn compose(&self, next: &RangeMap) -> RangeMap {
+        let mut entries: Vec<RangeEntry> = vec![];
+
+        for piece in self.pieces(0..u64::MAX) {
+            for next_piece in next.pieces(piece.dest()) {
+                let entry = RangeEntry {
+                    dest_start: next_piece.dest_start,
+                    source_start: piece.source_start + (next_piece.source_start - piece.dest_start),
+                    length: next_piece.length,
+                };
+
+                if entry.dest_start == entry.source_start {
+                    continue;
+                }
+
+                match entries.last_mut() {
+                    Some(last)
+                        if last.source().end == entry.source_start
+                            && last.dest().end == entry.dest_start =>
+                    {
+                        last.length += entry.length;
+                    }
+                    _ => entries.push(entry),
+                }
+            }
+        }
+
+        RangeMap { entries }
+    }
+
+    /// The map going the other way. Returns `None` unless every value has
+    /// exactly one value mapping to it, since otherwise there's no exact
+    /// inverse.
+    pub fn invert(&self) -> Option<RangeMap> {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(RangeEntry::swapped)
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.source_start);
+
+        // Values outside the entries map to themselves, so the destination
+        // ranges have to tile exactly the same values as the source ranges.
+        let mut sources = self
+            .entries
+            .iter()
+            .map(RangeEntry::source)
+            .collect::<Vec<_>>();
+        let mut dests = entries.iter().map(RangeEntry::source).collect::<Vec<_>>();
+
+        if dests.windows(2).any(|pair| pair[0].end > pair[1].start) {
+            return None;
+        }
+
+        merge_ranges(&mut sources);
+        merge_ranges(&mut dests);
+
+        (sources == dests).then_some(RangeMap { entries })
+    }
+
+    /// Every value that maps into one of `ranges`, as sorted ranges that
+    /// don't overlap or touch.
+    pub fn preimage(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let mut ranges = ranges.to_vec();
+        merge_ranges(&mut ranges);
+
+        let mut preimage = vec![];
+        for piece in self.pieces(0..u64::MAX) {
+            for range in ranges.iter() {
+                let start = range.start.max(piece.dest_start);
+                let end = range.end.min(piece.dest().end);
+
+                if start < end {
+                    let offset = piece.source_start;
+                    preimage.push(
+                        offset + (start - piece.dest_start)..offset + (end - piece.dest_start),
+                    );
+                }
+            }
+        }
+
+        merge_ranges(&mut preimage);
+        preimage
+    }
+
+    /// The entry that maps `value`, if any.
+    pub fn entry_for(&self, value: u64) -> Option<&RangeEntry> {
+        let i = self
+            .entries
+            .partition_point(|entry| entry.source_start <= value);
+
+        i.checked_sub(1)
+            .map(|i| &self.entries[i])
+            .filter(|entry| entry.source().contains(&value))
+    }
+
+    /// Splits `range` into pieces that are each mapped by a single entry, or
+    /// by none at all (in which case the piece maps to itself).
+    fn pieces(&self, range: Range<u64>) -> Vec<RangeEntry> {
+        let first = self
+            .entries
+            .partition_point(|entry| entry.source().end <= range.start);
+        let mut pieces = vec![];
+        let mut start = range.start;
+
+        for entry in self.entries[first..].iter() {
+            if entry.source_start >= range.end {
+                break;
+            }
+
+            if start < entry.source_start {
+                pieces.push(identity(start..entry.source_start));
+            }
+
+            pieces.extend(entry.restrict(range.clone()));
+            start = entry.source().end;
+        }
+
+        if start < range.end {
+            pieces.push(identity(start..range.end));
+        }
+
+        pieces
+    }
+
+    /// The parts of `range` that no entry covers.
+    fn gaps_within(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        self.pieces(range)
+            .into_iter()
+            .filter(|piece| self.entry_for(piece.source_start).is_none())
+            .map(|piece| piece.source())
+            .collect()
+    }
+}
+
+fn identity(range: Range<u64>) -> RangeEntry {
+    RangeEntry {
+        dest_start: range.start,
+        source_start: range.start,
+        length: range.end - range.start,
+    }
+}
+
+/// Sorts `ranges` and joins any that overlap or touch.
+pub fn merge_ranges(ranges: &mut Vec<Range<u64>>) {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<u64>> = vec![];
+    for range in ranges.drain(..) {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    *ranges = merged;
+}
+
+/// Writes the entries as almanac rows, one `dest_start source_start length`
+/// per line.
+impl fmt::Display for RangeMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(
+                f,
+                "{} {} {}",
+                entry.dest_start, entry.source_start, entry.length
+            )?;
+        }
+
+        Ok(())
+    }
+}
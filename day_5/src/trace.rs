@@ -0,0 +1,101 @@
+//! Following a single value through the almanac, keeping every stage along
+//! the way.
+
+use std::fmt::Write;
+
+use crate::{range_map::RangeEntry, Direction, Garden};
+
+/// One category along a traced path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStage {
+    pub category: String,
+    pub value: u64,
+    /// The map row that led to this stage. Where rows overlap, this is only
+    /// the part of the row that applies. `None` for the first stage and for
+    /// values that no row covers.
+    pub row: Option<RangeEntry>,
+}
+
+impl Garden {
+    /// Every stage `value` goes through on its way from category `from` to
+    /// category `to`, starting with `value` itself. Returns `None` if the two
+    /// categories aren't connected.
+    pub fn trace(&self, value: u64, from: &str, to: &str) -> Option<Vec<TraceStage>> {
+        let path = self.path(from, to)?;
+
+        let mut stages = vec![TraceStage {
+            category: from.to_string(),
+            value,
+            row: None,
+        }];
+
+        let mut value = value;
+        for step in path {
+            let category_map = &self.maps[step.map];
+            let range_map = category_map.get(step.direction);
+
+            let row = range_map
+                .entry_for(value)
+                .map(|entry| match step.direction {
+                    Direction::Forwards => *entry,
+                    Direction::Backwards => entry.swapped(),
+                });
+
+            value = range_map.get(value);
+            stages.push(TraceStage {
+                category: category_map.target(step.direction).to_string(),
+                value,
+                row,
+            });
+        }
+
+        Some(stages)
+    }
+
+    /// A CSV table tracing every seed to its location, with a column for each
+    /// stage's value and another for the row that led to it.
+    pub fn trace_seeds_csv(&self) -> String {
+        let traces = self
+            .seeds
+            .iter()
+            .map(|&seed| {
+                self.trace(seed, "seed", "location")
+                    .expect("Expected the maps to lead from seed to location")
+            })
+            .collect::<Vec<_>>();
+
+        let mut csv = String::new();
+
+        let Some(first) = traces.first() else {
+            return csv;
+        };
+
+        let header = first
+            .iter()
+            .enumerate()
+            .map(|(i, stage)| match i {
+                0 => stage.category.clone(),
+                _ => format!("{0},{0}_row", stage.category),
+            })
+            .collect::<Vec<_>>();
+        writeln!(csv, "{}", header.join(",")).unwrap();
+
+        for trace in traces.iter() {
+            let fields = trace
+                .iter()
+                .enumerate()
+                .map(|(i, stage)| match (i, stage.row) {
+                    (0, _) => stage.value.to_string(),
+                    (_, Some(row)) => format!(
+                        "{},{} {} {}",
+                        stage.value, row.dest_start, row.source_start, row.length
+                    ),
+                    (_, None) => format!("{},", stage.value),
+                })
+                .collect::<Vec<_>>();
+            writeln!(csv, "{}", fields.join(",")).unwrap();
+        }
+
+        csv
+    }
+}
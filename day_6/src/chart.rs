@@ -0,0 +1,325 @@
+//! Charts of distance against hold time for a single race, with the record
+//! drawn across them and the winning hold times shaded.
+
+use std::fmt::Write;
+
+use crate::{physics::Physics, RaceData};
+
+const ASCII_WIDTH: usize = 64;
+const ASCII_HEIGHT: usize = 16;
+/// Room for the distance labels to the left of the ASCII chart.
+const ASCII_LABEL_WIDTH: usize = 12;
+
+const SVG_WIDTH: f64 = 640.0;
+const SVG_HEIGHT: f64 = 360.0;
+const SVG_MARGIN_LEFT: f64 = 90.0;
+const SVG_MARGIN_RIGHT: f64 = 20.0;
+const SVG_MARGIN_TOP: f64 = 40.0;
+const SVG_MARGIN_BOTTOM: f64 = 50.0;
+/// The most points the SVG curve is drawn through.
+const SVG_SAMPLES: usize = 400;
+
+/// Draws the race as text: `*` for the distance curve, `-` for the record and
+/// `:` under the curve wherever the hold time wins.
+pub fn ascii(title: &str, race: &RaceData, physics: &dyn Physics) -> String {
+    let hold_times = sample_hold_times(race.time(), ASCII_WIDTH);
+    let distances = hold_times
+        .iter()
+        .map(|&hold_time| physics.distance(hold_time, race.time()))
+        .collect::<Vec<_>>();
+
+    let winning = race.winning_hold_times_with(physics);
+    let top = chart_top(&distances, race);
+    let row_of = |distance: u128| {
+        ((distance as f64 / top as f64) * (ASCII_HEIGHT - 1) as f64).round() as usize
+    };
+    let record_row = row_of(race.record_distance());
+
+    let mut out = String::new();
+    writeln!(out, "{title}").unwrap();
+
+    for row in (0..ASCII_HEIGHT).rev() {
+        let label = if row == ASCII_HEIGHT - 1 {
+            top.to_string()
+        } else if row == record_row {
+            race.record_distance().to_string()
+        } else if row == 0 {
+            "0".to_string()
+        } else {
+            String::new()
+        };
+        write!(out, "{label:>ASCII_LABEL_WIDTH$} |").unwrap();
+
+        for (&hold_time, &distance) in hold_times.iter().zip(distances.iter()) {
+            let curve_row = row_of(distance);
+            let wins = winning
+                .as_ref()
+                .is_some_and(|winning| winning.contains(&hold_time));
+
+            let ch = if row == curve_row {
+                '*'
+            } else if row == record_row {
+                '-'
+            } else if wins && row < curve_row {
+                ':'
+            } else {
+                ' '
+            };
+            out.push(ch);
+        }
+        out.push('\n');
+    }
+
+    writeln!(
+        out,
+        "{:>ASCII_LABEL_WIDTH$} +{}",
+        "",
+        "-".repeat(hold_times.len())
+    )
+    .unwrap();
+
+    let time = race.time().to_string();
+    let padding = hold_times.len().saturating_sub(time.len());
+    writeln!(out, "{:>ASCII_LABEL_WIDTH$}  0{time:>padding$}", "").unwrap();
+
+    writeln!(out, "{}", summary(race, physics)).unwrap();
+    out
+}
+
+/// Draws the race as a standalone SVG document.
+pub fn svg(title: &str, race: &RaceData, physics: &dyn Physics) -> String {
+    let hold_times = sample_hold_times(race.time(), SVG_SAMPLES);
+    let distances = hold_times
+        .iter()
+        .map(|&hold_time| physics.distance(hold_time, race.time()))
+        .collect::<Vec<_>>();
+
+    let top = chart_top(&distances, race);
+    let plot_width = SVG_WIDTH - SVG_MARGIN_LEFT - SVG_MARGIN_RIGHT;
+    let plot_height = SVG_HEIGHT - SVG_MARGIN_TOP - SVG_MARGIN_BOTTOM;
+    let bottom = SVG_MARGIN_TOP + plot_height;
+    let right = SVG_MARGIN_LEFT + plot_width;
+
+    let x_of = |hold_time: u128| {
+        SVG_MARGIN_LEFT + hold_time as f64 / race.time().max(1) as f64 * plot_width
+    };
+    let y_of = |distance: u128| bottom - distance as f64 / top as f64 * plot_height;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SVG_WIDTH}" height="{SVG_HEIGHT}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(out, "<title>{}</title>", xml_escape(title)).unwrap();
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    if let Some(winning) = race.winning_hold_times_with(physics) {
+        let (start, end) = (x_of(*winning.start()), x_of(*winning.end()));
+        writeln!(
+            out,
+            r##"<rect x="{start:.1}" y="{SVG_MARGIN_TOP}" width="{:.1}" height="{plot_height}" fill="#c8e6c9"/>"##,
+            (end - start).max(1.0)
+        )
+        .unwrap();
+    }
+
+    let points = hold_times
+        .iter()
+        .zip(distances.iter())
+        .map(|(&hold_time, &distance)| format!("{:.1},{:.1}", x_of(hold_time), y_of(distance)))
+        .collect::<Vec<_>>();
+    writeln!(
+        out,
+        r##"<polyline points="{}" fill="none" stroke="#1565c0" stroke-width="2"/>"##,
+        points.join(" ")
+    )
+    .unwrap();
+
+    let record_y = y_of(race.record_distance());
+    writeln!(
+        out,
+        r##"<line x1="{SVG_MARGIN_LEFT}" y1="{record_y:.1}" x2="{right}" y2="{record_y:.1}" stroke="#d32f2f" stroke-dasharray="6 4"/>"##
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r##"<text x="{right}" y="{:.1}" text-anchor="end" fill="#d32f2f">record {}</text>"##,
+        record_y - 4.0,
+        race.record_distance()
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        r#"<path d="M{SVG_MARGIN_LEFT},{SVG_MARGIN_TOP} V{bottom} H{right}" fill="none" stroke="black"/>"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{SVG_MARGIN_LEFT}" y="{}" text-anchor="middle">0</text>"#,
+        bottom + 16.0
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{right}" y="{}" text-anchor="middle">{}</text>"#,
+        bottom + 16.0,
+        race.time()
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle">hold time (ms)</text>"#,
+        SVG_MARGIN_LEFT + plot_width / 2.0,
+        bottom + 36.0
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="{bottom}" text-anchor="end">0</text>"#,
+        SVG_MARGIN_LEFT - 6.0
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="end">{top}</text>"#,
+        SVG_MARGIN_LEFT - 6.0,
+        SVG_MARGIN_TOP + 4.0
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle" transform="rotate(-90 {} {})">distance (mm)</text>"#,
+        16.0,
+        SVG_MARGIN_TOP + plot_height / 2.0,
+        16.0,
+        SVG_MARGIN_TOP + plot_height / 2.0
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        r#"<text x="{SVG_MARGIN_LEFT}" y="24" font-size="14" font-weight="bold">{}</text>"#,
+        xml_escape(title)
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{right}" y="24" text-anchor="end">{}</text>"#,
+        summary(race, physics)
+    )
+    .unwrap();
+    writeln!(out, "</svg>").unwrap();
+
+    out
+}
+
+fn summary(race: &RaceData, physics: &dyn Physics) -> String {
+    match race.winning_hold_times_with(physics) {
+        Some(winning) => format!(
+            "Holding for {}..={} ms wins ({} ways)",
+            winning.start(),
+            winning.end(),
+            winning.end() - winning.start() + 1
+        ),
+        None => "No hold time beats the record".to_string(),
+    }
+}
+
+/// Hold times spread evenly from `0` to `time`, at most `max_samples` of
+/// them.
+fn sample_hold_times(time: u128, max_samples: usize) -> Vec<u128> {
+    if time < max_samples as u128 {
+        return (0..=time).collect();
+    }
+
+    // Split `time * i / steps` up so that it can't overflow.
+    let steps = (max_samples - 1) as u128;
+    (0..=steps)
+        .map(|i| time / steps * i + time % steps * i / steps)
+        .collect()
+}
+
+/// The distance at the top of the chart, leaving room for the record line.
+fn chart_top(distances: &[u128], race: &RaceData) -> u128 {
+    distances
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(race.record_distance())
+        .max(1)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::LinearCharge;
+
+    #[test]
+    fn ascii_snapshot() {
+        let race = RaceData::create(7, 9);
+        let chart = ascii("Race 1", &race, &LinearCharge);
+
+        // Trailing spaces are trimmed so that they don't have to be kept in
+        // the snapshot.
+        let lines = chart.lines().map(str::trim_end).collect::<Vec<_>>();
+        let expected = "\
+Race 1
+          12 |   **
+             |   ::
+             |  *::*
+             |  ::::
+           9 |--------
+             |  ::::
+             |  ::::
+             | *::::*
+             |  ::::
+             |  ::::
+             |  ::::
+             |  ::::
+             |  ::::
+             |  ::::
+             |  ::::
+           0 |* :::: *
+             +--------
+              0      7
+Holding for 2..=5 ms wins (4 ways)
+";
+        assert_eq!(lines, expected.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn svg_snapshot() {
+        let race = RaceData::create(7, 9);
+
+        assert_eq!(
+            svg("Race 1", &race, &LinearCharge),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="640" height="360" font-family="sans-serif" font-size="12">
+<title>Race 1</title>
+<rect width="100%" height="100%" fill="white"/>
+<rect x="241.4" y="40" width="227.1" height="270" fill="#c8e6c9"/>
+<polyline points="90.0,310.0 165.7,175.0 241.4,85.0 317.1,40.0 392.9,40.0 468.6,85.0 544.3,175.0 620.0,310.0" fill="none" stroke="#1565c0" stroke-width="2"/>
+<line x1="90" y1="107.5" x2="620" y2="107.5" stroke="#d32f2f" stroke-dasharray="6 4"/>
+<text x="620" y="103.5" text-anchor="end" fill="#d32f2f">record 9</text>
+<path d="M90,40 V310 H620" fill="none" stroke="black"/>
+<text x="90" y="326" text-anchor="middle">0</text>
+<text x="620" y="326" text-anchor="middle">7</text>
+<text x="355" y="346" text-anchor="middle">hold time (ms)</text>
+<text x="84" y="310" text-anchor="end">0</text>
+<text x="84" y="44" text-anchor="end">12</text>
+<text x="16" y="175" text-anchor="middle" transform="rotate(-90 16 175)">distance (mm)</text>
+<text x="90" y="24" font-size="14" font-weight="bold">Race 1</text>
+<text x="620" y="24" text-anchor="end">Holding for 2..=5 ms wins (4 ways)</text>
+</svg>
+"##
+        );
+    }
+}
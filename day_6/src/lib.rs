@@ -0,0 +1,342 @@
+use std::{fmt, ops::RangeInclusive};
+
+pub mod chart;
+pub mod physics;
+pub mod portfolio;
+
+#[derive(Debug)]
+pub struct RaceData {
+    time: u128,
+    record_distance: u128,
+}
+
+#[derive(Debug)]
+pub struct Races {
+    races: Vec<RaceData>,
+    /// The times as written on the sheet, for [`Races::into_corrected`].
+    time_digits: Vec<String>,
+    /// The record distances as written on the sheet.
+    record_digits: Vec<String>,
+}
+
+/// How to read the sheet as a single race.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Correction {
+    /// Join the digits of every number, ignoring the spaces between them.
+    Concatenate,
+    /// Add the numbers up.
+    Sum,
+    /// Keep the biggest number.
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The sheet needs a line of times and then a line of record distances.
+    MissingLine,
+    /// A time or record distance that isn't a number that fits in a `u128`.
+    InvalidNumber(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorrectionError {
+    /// The corrected time or record doesn't fit in a `u128`.
+    Overflow,
+}
+
+impl RaceData {
+    pub fn time(&self) -> u128 {
+        self.time
+    }
+
+    pub fn record_distance(&self) -> u128 {
+        self.record_distance
+    }
+
+    /// Tries every hold time one by one. This takes as long as the race, so
+    /// it's only useful for cross-checking [`RaceData::winning_hold_times`].
+    pub fn find_hold_times_to_beat_record(&self) -> impl Iterator<Item = u128> + '_ {
+        let range = 1..=self.time;
+
+        range.filter(|&hold_time| self.beats_record(hold_time))
+    }
+
+    /// The shortest and longest hold times that beat the record, or `None` if
+    /// none do.
+    ///
+    /// The boat beats the record when `h * (T - h) > D`, i.e. when `h` lies
+    /// strictly between the roots of `h^2 - T*h + D`. Those are found with an
+    /// integer square root and then nudged onto the exact boundary, since the
+    /// square root is rounded down.
+    pub fn winning_hold_times(&self) -> Option<RangeInclusive<u128>> {
+        let time = self.time;
+
+        // The distance peaks at half the race time.
+        if !self.beats_record(time / 2) {
+            return None;
+        }
+
+        let discriminant = time
+            .checked_mul(time)
+            .zip(self.record_distance.checked_mul(4))
+            .map(|(time_squared, four_record)| time_squared - four_record);
+
+        let mut shortest = match discriminant {
+            Some(discriminant) => (time - discriminant.isqrt()) / 2,
+            None => self.bisect_shortest_hold_time(),
+        };
+
+        while !self.beats_record(shortest) {
+            shortest += 1;
+        }
+        while shortest > 0 && self.beats_record(shortest - 1) {
+            shortest -= 1;
+        }
+
+        // The curve is symmetric, so the longest hold time mirrors the
+        // shortest one.
+        Some(shortest..=time - shortest)
+    }
+
+    pub fn count_ways_to_beat_record(&self) -> u128 {
+        match self.winning_hold_times() {
+            Some(hold_times) => hold_times.end() - hold_times.start() + 1,
+            None => 0,
+        }
+    }
+
+    fn beats_record(&self, hold_time: u128) -> bool {
+        // A distance too big for a u128 certainly beats the record.
+        hold_time
+            .checked_mul(self.time - hold_time)
+            .is_none_or(|distance| distance > self.record_distance)
+    }
+
+    /// For races too long to square the time, binary searches the rising
+    /// half of the curve instead.
+    fn bisect_shortest_hold_time(&self) -> u128 {
+        let (mut lo, mut hi) = (0, self.time / 2);
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.beats_record(mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+
+        lo
+    }
+}
+
+impl RaceData {
+    fn create(time: u128, record_distance: u128) -> Self {
+        Self {
+            time,
+            record_distance,
+        }
+    }
+}
+
+impl Races {
+    pub fn create(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines();
+        let (Some(time_line), Some(record_line)) = (lines.next(), lines.next()) else {
+            return Err(ParseError::MissingLine);
+        };
+
+        let time_digits = get_digit_strings(time_line.trim_start_matches("Time:"));
+        let record_digits = get_digit_strings(record_line.trim_start_matches("Distance:"));
+
+        let races = time_digits
+            .iter()
+            .zip(record_digits.iter())
+            .map(|(time, distance)| Ok(RaceData::create(parse_num(time)?, parse_num(distance)?)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            races,
+            time_digits,
+            record_digits,
+        })
+    }
+
+    /// The single race that the sheet describes once the bad kerning is
+    /// accounted for.
+    pub fn into_corrected(self) -> Result<RaceData, CorrectionError> {
+        self.into_corrected_with(Correction::Concatenate)
+    }
+
+    pub fn into_corrected_with(self, correction: Correction) -> Result<RaceData, CorrectionError> {
+        let time = correct(&self.time_digits, correction)?;
+        let record_distance = correct(&self.record_digits, correction)?;
+
+        Ok(RaceData::create(time, record_distance))
+    }
+
+    pub fn races(&self) -> &[RaceData] {
+        &self.races
+    }
+
+    pub fn how_many_ways_to_beat_each_record(&self) -> Vec<u128> {
+        self.races
+            .iter()
+            .map(RaceData::count_ways_to_beat_record)
+            .collect()
+    }
+}
+
+/// Combines the numbers of one line of the sheet. Concatenating works on the
+/// digits as written, so a race with a time of `0` still adds a digit.
+fn correct(digit_strings: &[String], correction: Correction) -> Result<u128, CorrectionError> {
+    if digit_strings.is_empty() {
+        return Ok(0);
+    }
+
+    let mut nums = digit_strings.iter().map(|digits| digits.parse::<u128>());
+
+    match correction {
+        Correction::Concatenate => digit_strings
+            .concat()
+            .parse()
+            .map_err(|_| CorrectionError::Overflow),
+        Correction::Sum => nums.try_fold(0u128, |total, num| {
+            num.ok()
+                .and_then(|num| total.checked_add(num))
+                .ok_or(CorrectionError::Overflow)
+        }),
+        Correction::Max => nums.try_fold(0u128, |max, num| {
+            num.map(|num| max.max(num))
+                .map_err(|_| CorrectionError::Overflow)
+        }),
+    }
+}
+
+fn parse_num(digits: &str) -> Result<u128, ParseError> {
+    digits
+        .parse()
+        .map_err(|_| ParseError::InvalidNumber(digits.to_string()))
+}
+
+fn get_digit_strings(line: &str) -> Vec<String> {
+    line.split(' ')
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingLine => {
+                write!(f, "Expected a `Time:` line and then a `Distance:` line")
+            }
+            ParseError::InvalidNumber(digits) => {
+                write!(
+                    f,
+                    "Expected a number that fits in a u128 but found `{digits}`"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for CorrectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrectionError::Overflow => {
+                write!(f, "The corrected race is too long to fit in a u128")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CorrectionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200
+";
+
+    #[test]
+    fn closed_form_matches_brute_force() {
+        for time in 0..=80 {
+            for record_distance in 0..=time * time / 4 + 2 {
+                let race = RaceData::create(time, record_distance);
+                let brute_force = race.find_hold_times_to_beat_record().collect::<Vec<_>>();
+
+                let expected = brute_force
+                    .first()
+                    .zip(brute_force.last())
+                    .map(|(&lo, &hi)| lo..=hi);
+                assert_eq!(
+                    race.winning_hold_times(),
+                    expected,
+                    "time {time}, record {record_distance}"
+                );
+                assert_eq!(race.count_ways_to_beat_record(), brute_force.len() as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn example() {
+        let races = Races::create(EXAMPLE).unwrap();
+        assert_eq!(races.how_many_ways_to_beat_each_record(), [4, 8, 9]);
+
+        let race = races.into_corrected().unwrap();
+        assert_eq!(race.count_ways_to_beat_record(), 71503);
+    }
+
+    #[test]
+    fn corrections() {
+        let corrected = |input: &str, correction| {
+            let race = Races::create(input)
+                .unwrap()
+                .into_corrected_with(correction)?;
+            Ok((race.time(), race.record_distance()))
+        };
+
+        assert_eq!(corrected(EXAMPLE, Correction::Sum), Ok((52, 249)));
+        assert_eq!(corrected(EXAMPLE, Correction::Max), Ok((30, 200)));
+
+        // A zero still adds its digit.
+        let sheet = "Time: 7 0 30\nDistance: 9 0 200\n";
+        assert_eq!(corrected(sheet, Correction::Concatenate), Ok((7030, 90200)));
+        assert_eq!(corrected(sheet, Correction::Sum), Ok((37, 209)));
+
+        let max = u128::MAX;
+        let sheet = format!("Time: {max} 1\nDistance: 0 0\n");
+        assert_eq!(
+            corrected(&sheet, Correction::Sum),
+            Err(CorrectionError::Overflow)
+        );
+        assert_eq!(
+            corrected(&sheet, Correction::Concatenate),
+            Err(CorrectionError::Overflow)
+        );
+        assert_eq!(corrected(&sheet, Correction::Max), Ok((max, 0)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Races::create("Time: 7 15 30\n").unwrap_err(),
+            ParseError::MissingLine
+        );
+
+        let too_big = format!("{}0", u128::MAX);
+        assert_eq!(
+            Races::create(&format!("Time: 7 {too_big}\nDistance: 9 40\n")).unwrap_err(),
+            ParseError::InvalidNumber(too_big)
+        );
+    }
+}
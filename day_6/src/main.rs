@@ -1,19 +1,222 @@
-use std::fs;
+use std::{env, fs, ops::RangeInclusive, process};
 
-mod race;
+use day_6::{
+    chart,
+    physics::{self, LinearCharge, Physics},
+    portfolio::{self, Objective},
+    Correction, RaceData, Races,
+};
+
+/// Races longer than this are too slow to brute force with `--verify`.
+const VERIFY_TIME_LIMIT: u128 = 100_000_000;
 
 fn main() {
+    let mut verify = false;
+    let mut physics: Option<Box<dyn Physics>> = None;
+    let mut budget = None;
+    let mut objective = Objective::RecordsBeaten;
+    let mut correction = Correction::Concatenate;
+    let mut plot = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verify" => verify = true,
+            "--plot" => plot = true,
+            "--physics" => {
+                physics = Some(
+                    args.next()
+                        .as_deref()
+                        .and_then(physics::parse)
+                        .unwrap_or_else(|| usage()),
+                )
+            }
+            "--budget" => {
+                budget = Some(
+                    args.next()
+                        .and_then(|arg| arg.parse().ok())
+                        .unwrap_or_else(|| usage()),
+                )
+            }
+            "--objective" => {
+                objective = match args.next().as_deref() {
+                    Some("records") => Objective::RecordsBeaten,
+                    Some("margin") => Objective::TotalMargin,
+                    _ => usage(),
+                }
+            }
+            "--correction" => {
+                correction = match args.next().as_deref() {
+                    Some("concat") => Correction::Concatenate,
+                    Some("sum") => Correction::Sum,
+                    Some("max") => Correction::Max,
+                    _ => usage(),
+                }
+            }
+            _ => usage(),
+        }
+    }
+
     let input = fs::read_to_string("input.txt").expect("Couldn't find input file");
 
-    let races = race::Races::create(&input);
-    let ways_to_beat = races.how_many_ways_to_beat_each_record();
-    
-    let answer: u64 = ways_to_beat.into_iter().product();
+    let races = Races::create(&input).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    if plot {
+        let physics = physics.as_deref().unwrap_or(&LinearCharge);
+        for (i, race) in races.races().iter().enumerate() {
+            plot_race(
+                &format!("Race {}", i + 1),
+                &format!("race_{}.svg", i + 1),
+                race,
+                physics,
+            );
+        }
+
+        match races.into_corrected_with(correction) {
+            Ok(race) => plot_race("Corrected race", "race_corrected.svg", &race, physics),
+            Err(err) => eprintln!("{err}"),
+        }
+        return;
+    }
+
+    if let Some(budget) = budget {
+        print_plan(
+            &races,
+            budget,
+            objective,
+            physics.as_deref().unwrap_or(&LinearCharge),
+        );
+        return;
+    }
+    let ways_to_beat: Vec<u128> = match &physics {
+        Some(physics) => races
+            .races()
+            .iter()
+            .map(|race| race.count_ways_to_beat_record_with(physics.as_ref()))
+            .collect(),
+        None => races.how_many_ways_to_beat_each_record(),
+    };
+
+    let answer: u128 = ways_to_beat.into_iter().product();
     println!("Part 1 answer: {answer}");
 
-    let race = races.into_corrected();
-    let ways_to_beat = race.find_hold_times_to_beat_record();
-    
-    let answer = ways_to_beat.len();
+    if verify {
+        for (i, race) in races.races().iter().enumerate() {
+            verify_race(&format!("Race {}", i + 1), race, physics.as_deref());
+        }
+    }
+
+    let race = races.into_corrected_with(correction).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+    let answer = match &physics {
+        Some(physics) => race.count_ways_to_beat_record_with(physics.as_ref()),
+        None => race.count_ways_to_beat_record(),
+    };
     println!("Part 2 answer: {answer}");
+
+    if verify {
+        verify_race("Corrected race", &race, physics.as_deref());
+    }
+}
+
+/// Checks the winning hold times for `race` against trying every hold time.
+/// Without a physics model, the closed-form solution is also checked against
+/// bracketing with [`LinearCharge`].
+fn verify_race(name: &str, race: &RaceData, physics: Option<&dyn Physics>) {
+    let winning = match physics {
+        Some(physics) => race.winning_hold_times_with(physics),
+        None => {
+            let winning = race.winning_hold_times();
+            let bracketed = race.winning_hold_times_with(&LinearCharge);
+
+            if winning != bracketed {
+                eprintln!(
+                    "{name}: closed form gives {winning:?} but bracketing gives {bracketed:?}"
+                );
+                process::exit(1);
+            }
+
+            winning
+        }
+    };
+
+    if race.time() > VERIFY_TIME_LIMIT {
+        println!("{name}: too long to brute force");
+        return;
+    }
+
+    // Only the first and last winning hold times are kept, since there can
+    // be far too many to collect.
+    let expected = race
+        .find_hold_times_to_beat_record_with(physics.unwrap_or(&LinearCharge))
+        .fold(None, |winning: Option<RangeInclusive<u128>>, hold_time| {
+            Some(winning.map_or(hold_time, |winning| *winning.start())..=hold_time)
+        });
+
+    if winning != expected {
+        eprintln!("{name}: solver gives {winning:?} but brute force gives {expected:?}");
+        process::exit(1);
+    }
+
+    match winning {
+        Some(hold_times) => println!(
+            "{name}: holds {}..={} win, brute force agrees",
+            hold_times.start(),
+            hold_times.end()
+        ),
+        None => println!("{name}: nothing wins, brute force agrees"),
+    }
+}
+
+/// Prints the ASCII chart for `race` and writes the SVG one to `filename`.
+fn plot_race(title: &str, filename: &str, race: &RaceData, physics: &dyn Physics) {
+    println!("{}", chart::ascii(title, race, physics));
+
+    fs::write(filename, chart::svg(title, race, physics)).unwrap_or_else(|err| {
+        eprintln!("Couldn't write {filename}: {err}");
+        process::exit(1);
+    });
+    println!("Wrote {filename}\n");
+}
+
+fn print_plan(races: &Races, budget: u128, objective: Objective, physics: &dyn Physics) {
+    let plan = portfolio::optimise(races, budget, objective, physics).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+
+    for (i, (race, &hold_time)) in races.races().iter().zip(plan.hold_times.iter()).enumerate() {
+        let distance = physics.distance(hold_time, race.time());
+        let result = if distance > race.record_distance() {
+            format!(
+                "beats the record of {} by {}",
+                race.record_distance(),
+                distance - race.record_distance()
+            )
+        } else {
+            format!("doesn't beat the record of {}", race.record_distance())
+        };
+
+        println!(
+            "Race {}: hold for {hold_time} ms, travel {distance} mm, {result}",
+            i + 1
+        );
+    }
+
+    println!(
+        "{} records beaten by {} mm in total, holding for {} of {budget} ms",
+        plan.records_beaten, plan.total_margin, plan.total_hold_time
+    );
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: day_6 [--verify] [--physics linear|capped:<top speed>|quadratic|decay:<rate>,<half life>]");
+    eprintln!("             [--correction concat|sum|max] [--plot]");
+    eprintln!("       day_6 [--physics <...>] --budget <ms> [--objective records|margin]");
+    process::exit(2);
 }
@@ -0,0 +1,244 @@
+//! Different ways the boats could behave, and how to find the winning hold
+//! times for any of them.
+
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+use crate::RaceData;
+
+/// How far a boat travels in a race of `race_time` milliseconds when its
+/// button is held for `hold_time` of them.
+///
+/// Over hold times `0..=race_time`, the distance has to rise strictly up to
+/// its peak and never rise again after it. Winning hold times are found by
+/// bracketing, which relies on that shape. Distances too big for a `u128`
+/// should saturate.
+pub trait Physics {
+    fn distance(&self, hold_time: u128, race_time: u128) -> u128;
+}
+
+/// The puzzle's boats: every millisecond held adds 1 mm/ms of speed.
+#[derive(Debug, Clone, Copy)]
+pub struct LinearCharge;
+
+/// Like [`LinearCharge`], but the boat can't go faster than `top_speed`.
+#[derive(Debug, Clone, Copy)]
+pub struct CappedSpeed {
+    pub top_speed: u128,
+}
+
+/// The speed grows with the square of the hold time.
+#[derive(Debug, Clone, Copy)]
+pub struct QuadraticAcceleration;
+
+/// Holding adds `initial_rate` mm/ms of speed per millisecond at first, but
+/// that rate halves (rounding down) every `half_life` milliseconds.
+#[derive(Debug, Clone, Copy)]
+pub struct ChargeDecay {
+    pub initial_rate: u128,
+    pub half_life: u128,
+}
+
+impl Physics for LinearCharge {
+    fn distance(&self, hold_time: u128, race_time: u128) -> u128 {
+        hold_time.saturating_mul(race_time - hold_time)
+    }
+}
+
+impl Physics for CappedSpeed {
+    fn distance(&self, hold_time: u128, race_time: u128) -> u128 {
+        hold_time
+            .min(self.top_speed)
+            .saturating_mul(race_time - hold_time)
+    }
+}
+
+impl Physics for QuadraticAcceleration {
+    fn distance(&self, hold_time: u128, race_time: u128) -> u128 {
+        hold_time
+            .saturating_mul(hold_time)
+            .saturating_mul(race_time - hold_time)
+    }
+}
+
+impl Physics for ChargeDecay {
+    fn distance(&self, hold_time: u128, race_time: u128) -> u128 {
+        let half_lives = hold_time / self.half_life;
+        let rest = hold_time % self.half_life;
+
+        // Past 128 halvings the rate is zero anyway.
+        let mut speed = 0u128;
+        for halvings in 0..half_lives.min(128) {
+            speed =
+                speed.saturating_add(self.half_life.saturating_mul(self.initial_rate >> halvings));
+        }
+
+        let final_rate = self
+            .initial_rate
+            .checked_shr(half_lives.min(128) as u32)
+            .unwrap_or(0);
+        speed = speed.saturating_add(rest.saturating_mul(final_rate));
+
+        speed.saturating_mul(race_time - hold_time)
+    }
+}
+
+/// Parses a physics model given on the command line: `linear`,
+/// `capped:<top speed>`, `quadratic` or `decay:<initial rate>,<half life>`.
+pub fn parse(input: &str) -> Option<Box<dyn Physics>> {
+    match input {
+        "linear" => Some(Box::new(LinearCharge)),
+        "quadratic" => Some(Box::new(QuadraticAcceleration)),
+        _ => {
+            if let Some(top_speed) = input.strip_prefix("capped:") {
+                let top_speed = top_speed.trim().parse().ok()?;
+                return Some(Box::new(CappedSpeed { top_speed }));
+            }
+
+            let (initial_rate, half_life) = input.strip_prefix("decay:")?.split_once(',')?;
+            let initial_rate = initial_rate.trim().parse().ok()?;
+            let half_life = half_life
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&half_life| half_life > 0)?;
+
+            Some(Box::new(ChargeDecay {
+                initial_rate,
+                half_life,
+            }))
+        }
+    }
+}
+
+impl RaceData {
+    /// The shortest and longest hold times that beat the record when the
+    /// boat follows `physics`, or `None` if none do.
+    pub fn winning_hold_times_with(&self, physics: &dyn Physics) -> Option<RangeInclusive<u128>> {
+        let distance = |hold_time| physics.distance(hold_time, self.time);
+        let beats_record = |hold_time| distance(hold_time) > self.record_distance;
+
+        // Ternary search for the peak. When the two probes tie, the peak
+        // can't be after the second one: the curve only rises strictly.
+        let (mut lo, mut hi) = (0, self.time);
+        while hi - lo > 2 {
+            let third = (hi - lo) / 3;
+            let (left, right) = (lo + third, hi - third);
+
+            match distance(left).cmp(&distance(right)) {
+                Ordering::Less => lo = left + 1,
+                Ordering::Greater => hi = right - 1,
+                Ordering::Equal => hi = right,
+            }
+        }
+
+        let peak = (lo..=hi).max_by_key(|&hold_time| distance(hold_time))?;
+        if !beats_record(peak) {
+            return None;
+        }
+
+        let shortest = partition_point(0, peak, |hold_time| !beats_record(hold_time));
+        let longest = if beats_record(self.time) {
+            self.time
+        } else {
+            partition_point(peak, self.time, beats_record) - 1
+        };
+
+        Some(shortest..=longest)
+    }
+
+    /// Like [`RaceData::find_hold_times_to_beat_record`], for cross-checking
+    /// [`RaceData::winning_hold_times_with`].
+    pub fn find_hold_times_to_beat_record_with<'a>(
+        &'a self,
+        physics: &'a dyn Physics,
+    ) -> impl Iterator<Item = u128> + 'a {
+        (0..=self.time)
+            .filter(move |&hold_time| physics.distance(hold_time, self.time) > self.record_distance)
+    }
+
+    pub fn count_ways_to_beat_record_with(&self, physics: &dyn Physics) -> u128 {
+        match self.winning_hold_times_with(physics) {
+            Some(hold_times) => hold_times.end() - hold_times.start() + 1,
+            None => 0,
+        }
+    }
+}
+
+/// The first value in `lo..hi` that `pred` is false for, or `hi` if there
+/// isn't one. `pred` has to be true for some prefix of the range and false
+/// for the rest.
+fn partition_point(mut lo: u128, mut hi: u128, pred: impl Fn(u128) -> bool) -> u128 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bracketing_matches_brute_force() {
+        let models: [&dyn Physics; 9] = [
+            &LinearCharge,
+            &CappedSpeed { top_speed: 0 },
+            &CappedSpeed { top_speed: 5 },
+            &CappedSpeed { top_speed: 40 },
+            &QuadraticAcceleration,
+            &ChargeDecay {
+                initial_rate: 1,
+                half_life: 1,
+            },
+            &ChargeDecay {
+                initial_rate: 8,
+                half_life: 3,
+            },
+            &ChargeDecay {
+                initial_rate: 100,
+                half_life: 10,
+            },
+            &ChargeDecay {
+                initial_rate: 3,
+                half_life: 100,
+            },
+        ];
+
+        for physics in models {
+            for time in 0..60 {
+                // The winning hold times only change when the record passes
+                // one of the distances, so those are the records worth trying.
+                let records = (0..=time)
+                    .map(|hold_time| physics.distance(hold_time, time))
+                    .flat_map(|distance| [distance.saturating_sub(1), distance]);
+
+                for record_distance in records {
+                    let race = RaceData::create(time, record_distance);
+                    let brute_force = race
+                        .find_hold_times_to_beat_record_with(physics)
+                        .collect::<Vec<_>>();
+
+                    let expected = brute_force
+                        .first()
+                        .zip(brute_force.last())
+                        .map(|(&lo, &hi)| lo..=hi);
+                    assert_eq!(
+                        race.winning_hold_times_with(physics),
+                        expected,
+                        "time {time}, record {record_distance}"
+                    );
+                    assert_eq!(
+                        race.count_ways_to_beat_record_with(physics),
+                        brute_force.len() as u128
+                    );
+                }
+            }
+        }
+    }
+}
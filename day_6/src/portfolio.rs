@@ -0,0 +1,237 @@
+//! Choosing hold times for a whole sheet of races when the button can only be
+//! held for so long in total.
+//!
+//! This is a knapsack where every race is a group of items (one per hold
+//! time) and exactly one item is taken from each group. The table has a row
+//! per race and a column per millisecond of budget, so it's meant for sheets
+//! like the puzzle's first part rather than for one huge corrected race.
+
+use std::fmt;
+
+use crate::{physics::Physics, Races};
+
+/// The most budget the optimiser will build a table for, after capping it at
+/// the total time of every race.
+const BUDGET_LIMIT: u128 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// Beat as many records as possible, then win by as much as possible.
+    RecordsBeaten,
+    /// Win by as much as possible in total, then beat as many records as
+    /// possible. Races that aren't won count as a margin of 0, and the total
+    /// saturates at `u128::MAX`.
+    TotalMargin,
+}
+
+/// The chosen hold times and how they do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// One hold time per race, in the order of the sheet.
+    pub hold_times: Vec<u128>,
+    pub records_beaten: usize,
+    pub total_margin: u128,
+    pub total_hold_time: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanError {
+    /// The budget is too big to tabulate.
+    BudgetTooLarge,
+}
+
+/// The hold times that do best by `objective` without holding for more than
+/// `budget` milliseconds across all of `races`. Ties go to the plan that
+/// holds for less time in total.
+pub fn optimise(
+    races: &Races,
+    budget: u128,
+    objective: Objective,
+    physics: &dyn Physics,
+) -> Result<Plan, PlanError> {
+    let total_time = races
+        .races()
+        .iter()
+        .fold(0u128, |total, race| total.saturating_add(race.time()));
+
+    let budget = budget.min(total_time);
+    if budget > BUDGET_LIMIT {
+        return Err(PlanError::BudgetTooLarge);
+    }
+    let budget = budget as usize;
+
+    // `best[b]` is the best score for the races so far using at most `b`
+    // milliseconds, and `choices[i][b]` is the hold time picked for race `i`
+    // to get there.
+    let mut best = vec![(0u128, 0u128); budget + 1];
+    let mut choices = vec![];
+
+    for race in races.races() {
+        let max_hold = race.time().min(budget as u128) as usize;
+        let scores = (0..=max_hold)
+            .map(|hold_time| {
+                let distance = physics.distance(hold_time as u128, race.time());
+                let margin = distance.saturating_sub(race.record_distance());
+                score(objective, u128::from(margin > 0), margin)
+            })
+            .collect::<Vec<_>>();
+
+        let mut next_best = vec![(0, 0); budget + 1];
+        let mut next_choices = vec![0; budget + 1];
+
+        for b in 0..=budget {
+            let mut chosen = None;
+
+            for (hold_time, &(primary, secondary)) in scores.iter().enumerate().take(b + 1) {
+                let (prev_primary, prev_secondary) = best[b - hold_time];
+                let total = (
+                    prev_primary.saturating_add(primary),
+                    prev_secondary.saturating_add(secondary),
+                );
+
+                if chosen.is_none_or(|(best_total, _)| total > best_total) {
+                    chosen = Some((total, hold_time));
+                }
+            }
+
+            let (total, hold_time) = chosen.unwrap();
+            next_best[b] = total;
+            next_choices[b] = hold_time;
+        }
+
+        best = next_best;
+        choices.push(next_choices);
+    }
+
+    // The smallest budget that reaches the best score.
+    let best_score = best[budget];
+    let mut b = best.iter().position(|&score| score == best_score).unwrap();
+
+    let mut hold_times = vec![0; choices.len()];
+    for (i, race_choices) in choices.iter().enumerate().rev() {
+        hold_times[i] = race_choices[b];
+        b -= race_choices[b];
+    }
+
+    Ok(evaluate(races, hold_times, physics))
+}
+
+/// Scores `(primary, secondary)`, where bigger is better.
+fn score(objective: Objective, records_beaten: u128, margin: u128) -> (u128, u128) {
+    match objective {
+        Objective::RecordsBeaten => (records_beaten, margin),
+        Objective::TotalMargin => (margin, records_beaten),
+    }
+}
+
+fn evaluate(races: &Races, hold_times: Vec<usize>, physics: &dyn Physics) -> Plan {
+    let mut plan = Plan {
+        hold_times: vec![],
+        records_beaten: 0,
+        total_margin: 0,
+        total_hold_time: 0,
+    };
+
+    for (race, hold_time) in races.races().iter().zip(hold_times) {
+        let hold_time = hold_time as u128;
+        let margin = physics
+            .distance(hold_time, race.time())
+            .saturating_sub(race.record_distance());
+
+        if margin > 0 {
+            plan.records_beaten += 1;
+        }
+        plan.total_margin = plan.total_margin.saturating_add(margin);
+        plan.total_hold_time += hold_time;
+        plan.hold_times.push(hold_time);
+    }
+
+    plan
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::BudgetTooLarge => {
+                write!(f, "Budgets over {BUDGET_LIMIT} ms are too big to plan for")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::LinearCharge;
+
+    #[test]
+    fn objectives_trade_records_for_margin() {
+        let races = Races::create("Time: 7 30\nDistance: 9 200\n").unwrap();
+
+        let plan = optimise(&races, 14, Objective::RecordsBeaten, &LinearCharge).unwrap();
+        assert_eq!(plan.hold_times, [2, 12]);
+        assert_eq!((plan.records_beaten, plan.total_margin), (2, 17));
+
+        let plan = optimise(&races, 14, Objective::TotalMargin, &LinearCharge).unwrap();
+        assert_eq!(plan.hold_times, [0, 14]);
+        assert_eq!((plan.records_beaten, plan.total_margin), (1, 24));
+    }
+
+    #[test]
+    fn ties_go_to_the_shorter_hold() {
+        // Holding for 3 or 4 ms goes just as far, and nothing beats 100 mm in
+        // 5 ms, so that race isn't worth holding for at all.
+        let races = Races::create("Time: 7 5\nDistance: 9 100\n").unwrap();
+
+        for objective in [Objective::RecordsBeaten, Objective::TotalMargin] {
+            let plan = optimise(&races, 12, objective, &LinearCharge).unwrap();
+            assert_eq!(plan.hold_times, [3, 0]);
+            assert_eq!(plan.total_hold_time, 3);
+        }
+    }
+
+    #[test]
+    fn matches_trying_every_plan() {
+        let races = Races::create("Time: 7 15 30\nDistance: 9 40 200\n").unwrap();
+
+        for objective in [Objective::RecordsBeaten, Objective::TotalMargin] {
+            for budget in 0..=52 {
+                let mut best: Option<Plan> = None;
+                for first in 0..=7 {
+                    for second in 0..=15 {
+                        for third in 0..=30 {
+                            if first + second + third > budget {
+                                continue;
+                            }
+
+                            let plan = evaluate(&races, vec![first, second, third], &LinearCharge);
+                            let key = |plan: &Plan| {
+                                let (primary, secondary) = score(
+                                    objective,
+                                    plan.records_beaten as u128,
+                                    plan.total_margin,
+                                );
+                                (primary, secondary, std::cmp::Reverse(plan.total_hold_time))
+                            };
+
+                            if best.as_ref().is_none_or(|best| key(&plan) > key(best)) {
+                                best = Some(plan);
+                            }
+                        }
+                    }
+                }
+
+                let best = best.unwrap();
+                let plan = optimise(&races, budget as u128, objective, &LinearCharge).unwrap();
+                assert_eq!(
+                    (plan.records_beaten, plan.total_margin, plan.total_hold_time),
+                    (best.records_beaten, best.total_margin, best.total_hold_time),
+                    "{objective:?} with {budget} ms"
+                );
+                assert!(plan.total_hold_time <= budget as u128);
+            }
+        }
+    }
+}